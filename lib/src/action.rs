@@ -0,0 +1,315 @@
+use crate::{ActionError, Game, GameConfig, PrivComId, PubComId, ReplayError, TrainId};
+use serde::{Deserialize, Serialize};
+
+/// Represents a move by the current player of a game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    /// Bids `amount` on `private`
+    BidPriv {
+        /// The private company bid on
+        private: PrivComId,
+        /// The amount bid
+        amount: u32,
+    },
+    /// Buys the cheapest private company
+    BuyCheapestPriv,
+    /// Passes
+    Pass,
+    /// Buys a share of the public company from the IPO
+    BuyIpoShare(PubComId),
+    /// Buys a share of the public company from the bank pool
+    BuyPoolShare(PubComId),
+    /// Buys the presidency of `pub_com`, setting the par value to `par`
+    BuyPresidency {
+        /// The public company
+        pub_com: PubComId,
+        /// The par value
+        par: u32,
+    },
+    /// Sells `count` shares of `pub_com`
+    SellShares {
+        /// The public company
+        pub_com: PubComId,
+        /// The number of shares
+        count: u32,
+    },
+    /// Ends the turn
+    EndTurn,
+    /// Lays tile `tile` rotated `rotation` steps clockwise at `x`, `y`
+    LayTile {
+        /// The column of the hex
+        x: usize,
+        /// The row of the hex
+        y: usize,
+        /// The id of the tile
+        tile: i32,
+        /// The rotation of the tile
+        rotation: u32,
+    },
+    /// Places a station in city `city` at `x`, `y`
+    PlaceStation {
+        /// The column of the hex
+        x: usize,
+        /// The row of the hex
+        y: usize,
+        /// The index of the city in the hex
+        city: usize,
+    },
+    /// Runs trains, paying the revenue out if `payout` and otherwise withholding it
+    RunTrains {
+        /// Whether to pay the revenue out
+        payout: bool,
+    },
+    /// Buys the next train from the depot
+    BuyTrain,
+    /// Buys the train from the open market
+    BuyMarketTrain(TrainId),
+    /// Buys `train` from `seller` for `price`
+    BuyPubComTrain {
+        /// The selling public company
+        seller: PubComId,
+        /// The train
+        train: TrainId,
+        /// The price
+        price: u32,
+    },
+    /// Declares bankruptcy
+    DeclareBankruptcy,
+    /// Buys `private` from the player owning it for `price`
+    BuyPriv {
+        /// The private company
+        private: PrivComId,
+        /// The price
+        price: u32,
+    },
+    /// Sets the par value of `pub_com`, the presidency of which came with a private company, to
+    /// `par`
+    SetPar {
+        /// The public company
+        pub_com: PubComId,
+        /// The par value
+        par: u32,
+    },
+    /// Lays tile `tile` rotated `rotation` steps clockwise at `x`, `y` using the ability of
+    /// `private`
+    LayPrivTile {
+        /// The private company
+        private: PrivComId,
+        /// The column of the hex
+        x: usize,
+        /// The row of the hex
+        y: usize,
+        /// The id of the tile
+        tile: i32,
+        /// The rotation of the tile
+        rotation: u32,
+    },
+}
+
+impl Game {
+    /// Returns whether performing `action` is allowed
+    pub fn action_allowed(&self, action: Action) -> bool {
+        self.check_action(action).is_ok()
+    }
+
+    /// Returns `Ok` if performing `action` is allowed, otherwise the reason it is not
+    pub fn check_action(&self, action: Action) -> Result<(), ActionError> {
+        if self.is_over() {
+            return Err(ActionError::GameOver);
+        }
+        match action {
+            Action::BidPriv { private, amount } => self.check_bid_priv(private, amount),
+            Action::BuyCheapestPriv => self.check_buy_cheapest_priv(),
+            Action::Pass => self.check_pass(),
+            Action::BuyIpoShare(pub_com) => self.check_buy_ipo_share(pub_com),
+            Action::BuyPoolShare(pub_com) => self.check_buy_pool_share(pub_com),
+            Action::BuyPresidency { pub_com, par } => self.check_buy_presidency(pub_com, par),
+            Action::SellShares { pub_com, count } => self.check_sell_shares(pub_com, count),
+            Action::EndTurn => self.check_end_turn(),
+            Action::LayTile {
+                x,
+                y,
+                tile,
+                rotation,
+            } => self.check_lay_tile(x, y, tile, rotation),
+            Action::PlaceStation { x, y, city } => self.check_place_station(x, y, city),
+            Action::RunTrains { .. } => self.check_run_trains(),
+            Action::BuyTrain => self.check_buy_train(),
+            Action::BuyMarketTrain(train) => self.check_buy_market_train(train),
+            Action::BuyPubComTrain {
+                seller,
+                train,
+                price,
+            } => self.check_buy_pub_com_train(seller, train, price),
+            Action::DeclareBankruptcy => self.check_declare_bankruptcy(),
+            Action::BuyPriv { private, price } => self.check_buy_priv(private, price),
+            Action::SetPar { pub_com, par } => self.check_set_par(pub_com, par),
+            Action::LayPrivTile {
+                private,
+                x,
+                y,
+                tile,
+                rotation,
+            } => self.check_lay_priv_tile(private, x, y, tile, rotation),
+        }
+    }
+
+    /// Performs `action` as the current player and appends it to the log, discarding any undone
+    /// actions
+    pub fn apply(&mut self, action: Action) -> Result<(), ActionError> {
+        self.perform(action)?;
+        self.redo_log.clear();
+        Ok(())
+    }
+
+    pub(crate) fn perform(&mut self, action: Action) -> Result<(), ActionError> {
+        let player = self.current_player;
+        match action {
+            Action::BidPriv { private, amount } => self.bid_priv(private, amount),
+            Action::BuyCheapestPriv => self.buy_cheapest_priv(),
+            Action::Pass => self.pass(),
+            Action::BuyIpoShare(pub_com) => self.buy_ipo_share(pub_com),
+            Action::BuyPoolShare(pub_com) => self.buy_pool_share(pub_com),
+            Action::BuyPresidency { pub_com, par } => self.buy_presidency(pub_com, par),
+            Action::SellShares { pub_com, count } => self.sell_shares(pub_com, count),
+            Action::EndTurn => self.end_turn(),
+            Action::LayTile {
+                x,
+                y,
+                tile,
+                rotation,
+            } => self.lay_tile(x, y, tile, rotation),
+            Action::PlaceStation { x, y, city } => self.place_station(x, y, city),
+            Action::RunTrains { payout } => self.run_trains(payout),
+            Action::BuyTrain => self.buy_train(),
+            Action::BuyMarketTrain(train) => self.buy_market_train(train),
+            Action::BuyPubComTrain {
+                seller,
+                train,
+                price,
+            } => self.buy_pub_com_train(seller, train, price),
+            Action::DeclareBankruptcy => self.declare_bankruptcy(),
+            Action::BuyPriv { private, price } => self.buy_priv(private, price),
+            Action::SetPar { pub_com, par } => self.set_par(pub_com, par),
+            Action::LayPrivTile {
+                private,
+                x,
+                y,
+                tile,
+                rotation,
+            } => self.lay_priv_tile(private, x, y, tile, rotation),
+        }?;
+        debug_assert!(self.capital_conserved());
+        self.log.push((player, action));
+        Ok(())
+    }
+
+    /// Returns the actions applied to this `Game` in order, each with the player performing it
+    pub fn log(&self) -> &[(usize, Action)] {
+        &self.log
+    }
+
+    /// Returns the game for `player_count` players using `config` after applying the actions of
+    /// `log` in order, each of which must be performed by the player it is logged with, as
+    /// returned by `log`
+    ///
+    /// The public company that comes with Cornelius Vanderbilt is random unless `config` sets it,
+    /// so `config` should set it to the `cv_pub_com` of the original game.
+    pub fn replay(
+        player_count: usize,
+        config: &GameConfig,
+        log: &[(usize, Action)],
+    ) -> Result<Self, ReplayError> {
+        let mut game = Self::with_config(player_count, config).map_err(ReplayError::Config)?;
+        for (index, &(player, action)) in log.iter().enumerate() {
+            game.check_current_player(player)
+                .and_then(|_| game.apply(action))
+                .map_err(|error| ReplayError::Action { index, error })?;
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Action;
+    use crate::game::tests;
+    use crate::{ActionError, Game, GameConfig, PrivComId, ReplayError};
+
+    fn played_game() -> Game {
+        let mut game = tests::game(4);
+        game.apply(Action::BuyCheapestPriv).unwrap();
+        game.apply(Action::BidPriv {
+            private: PrivComId::BAndO,
+            amount: 105,
+        })
+        .unwrap();
+        game.apply(Action::Pass).unwrap();
+        game
+    }
+
+    #[test]
+    fn apply_logs_performed_actions_only() {
+        let mut game = played_game();
+        assert_eq!(game.apply(Action::EndTurn), Err(ActionError::WrongRound));
+        assert_eq!(
+            game.log(),
+            &[
+                (0, Action::BuyCheapestPriv),
+                (
+                    1,
+                    Action::BidPriv {
+                        private: PrivComId::BAndO,
+                        amount: 105
+                    }
+                ),
+                (2, Action::Pass),
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_restores_the_game() {
+        let game = played_game();
+        let config = GameConfig::new().cv_pub_com(game.cv_pub_com());
+        assert_eq!(Game::replay(4, &config, game.log()), Ok(game));
+    }
+
+    #[test]
+    fn replay_restores_the_random_cv_pub_com() {
+        let mut game = Game::with_config(4, &GameConfig::new()).unwrap();
+        game.apply(Action::BuyCheapestPriv).unwrap();
+        let config = GameConfig::new().cv_pub_com(game.cv_pub_com());
+        assert_eq!(Game::replay(4, &config, game.log()), Ok(game));
+    }
+
+    #[test]
+    fn replay_rejects_actions_by_other_players() {
+        let mut log = played_game().log().to_vec();
+        log[1].0 = 3;
+        assert_eq!(
+            Game::replay(4, &GameConfig::new(), &log),
+            Err(ReplayError::Action {
+                index: 1,
+                error: ActionError::NotYourTurn
+            })
+        );
+    }
+
+    #[test]
+    fn replay_rejects_illegal_actions() {
+        let mut log = played_game().log().to_vec();
+        log.push((3, Action::EndTurn));
+        assert_eq!(
+            Game::replay(4, &GameConfig::new(), &log),
+            Err(ReplayError::Action {
+                index: 3,
+                error: ActionError::WrongRound
+            })
+        );
+        assert!(matches!(
+            Game::replay(7, &GameConfig::new(), &log),
+            Err(ReplayError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+/// Represents the bank of a game, which every payment to or from a player or public company is
+/// made with, except payments between them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bank {
+    size: u32,
+    capital: u32,
+    overdraft: u32,
+    broken: bool,
+}
+
+impl Bank {
+    pub(crate) fn new(size: u32) -> Self {
+        Self {
+            size,
+            capital: size,
+            overdraft: 0,
+            broken: false,
+        }
+    }
+
+    /// Pays `amount` out of this `Bank`, breaking it if its capital runs out and covering any
+    /// missing capital with an overdraft
+    pub(crate) fn withdraw(&mut self, amount: u32) {
+        if amount >= self.capital && amount > 0 {
+            self.broken = true;
+        }
+        let capital = self.capital.min(amount);
+        self.capital -= capital;
+        self.overdraft += amount - capital;
+    }
+
+    /// Pays `amount` into this `Bank`, settling any overdraft first
+    pub(crate) fn deposit(&mut self, amount: u32) {
+        let settled = self.overdraft.min(amount);
+        self.overdraft -= settled;
+        self.capital += amount - settled;
+    }
+
+    /// Returns the capital this `Bank` started with
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the capital left in this `Bank`
+    pub fn capital(&self) -> u32 {
+        self.capital
+    }
+
+    /// Returns the capital paid out by this `Bank` beyond its capital after it broke
+    pub fn overdraft(&self) -> u32 {
+        self.overdraft
+    }
+
+    /// Returns whether this `Bank` has run out of capital
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bank;
+
+    #[test]
+    fn withdraw_and_deposit() {
+        let mut bank = Bank::new(100);
+        bank.withdraw(40);
+        assert_eq!(bank.capital(), 60);
+        bank.deposit(10);
+        assert_eq!(bank.capital(), 70);
+        assert_eq!(bank.overdraft(), 0);
+        assert!(!bank.is_broken());
+    }
+
+    #[test]
+    fn withdrawing_all_capital_breaks_the_bank() {
+        let mut bank = Bank::new(100);
+        bank.withdraw(100);
+        assert_eq!(bank.capital(), 0);
+        assert!(bank.is_broken());
+    }
+
+    #[test]
+    fn overdraft_is_settled_before_capital() {
+        let mut bank = Bank::new(100);
+        bank.withdraw(130);
+        assert_eq!(bank.capital(), 0);
+        assert_eq!(bank.overdraft(), 30);
+        assert!(bank.is_broken());
+        bank.deposit(50);
+        assert_eq!(bank.overdraft(), 0);
+        assert_eq!(bank.capital(), 20);
+        assert!(bank.is_broken());
+        assert_eq!(bank.size(), 100);
+    }
+}
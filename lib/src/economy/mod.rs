@@ -1,11 +1,13 @@
 //! Game economy logic
 
+mod bank;
 mod par_track;
 mod player;
 mod pub_com;
 mod shares;
 mod stock_chart;
 
+pub use bank::Bank;
 pub use par_track::ParTrack;
 pub use player::Player;
 pub use pub_com::PubCom;
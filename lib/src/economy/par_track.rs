@@ -1,36 +1,41 @@
-use crate::PubComId;
-use crate::INVALID_TOML;
+use crate::toml_reader::{index_path, TomlReader};
+use crate::{ConfigError, PubComId};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use toml::Value;
 
-static VALUES_MISSING: &str = "values is missing";
-static VALUES_TYPEERROR: &str = "values is not of type Array";
-static VALUE_TYPEERROR: &str = "value is not of type Integer";
-
 /// Represents the par track for a game
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ParTrack {
     values: Vec<u32>,
+    #[serde(serialize_with = "crate::sorted_serde::map")]
     tokens: HashMap<PubComId, usize>,
 }
 
 impl ParTrack {
-    pub(crate) fn from_toml(s: &str) -> Self {
-        let toml = s.parse::<Value>().expect(INVALID_TOML);
+    /// Returns the `ParTrack` described by the TOML in `s`, naming `file` in any `ConfigError`
+    pub fn from_toml(file: &str, s: &str) -> Result<Self, Vec<ConfigError>> {
+        let mut reader = TomlReader::new(file);
+        let par_track = reader
+            .parse(s)
+            .and_then(|toml| Self::read(&toml, &mut reader));
+        reader.finish(par_track)
+    }
+
+    fn read(toml: &Value, reader: &mut TomlReader) -> Option<Self> {
         let mut values = Vec::new();
-        let values_toml = toml.get("values").expect(VALUES_MISSING);
-        for value in values_toml.as_array().expect(VALUES_TYPEERROR) {
-            let value = value.as_integer().expect(VALUE_TYPEERROR);
-            values.push(value as u32);
+        for (i, value) in reader.array(toml, "", "values")?.iter().enumerate() {
+            values.push(reader.as_unsigned(value, &index_path("values", i)));
         }
-        Self {
-            values,
+        Some(Self {
+            values: values.into_iter().collect::<Option<Vec<_>>>()?,
             tokens: HashMap::new(),
-        }
+        })
     }
 
     pub(crate) fn add_token(&mut self, pub_com: PubComId, par: u32) {
-        self.tokens.insert(pub_com, self.par_to_position(par));
+        let position = self.values.iter().position(|&v| v == par).unwrap();
+        self.tokens.insert(pub_com, position);
     }
 
     /// Returns the par value of `pub_com` on this `ParTrack`
@@ -42,13 +47,4 @@ impl ParTrack {
     pub fn values(&self) -> &[u32] {
         &self.values
     }
-
-    fn par_to_position(&self, par: u32) -> usize {
-        match par {
-            70 => 0,
-            80 => 1,
-            95 => 2,
-            _ => unreachable!(),
-        }
-    }
 }
@@ -1,13 +1,15 @@
 use crate::economy::Shares;
 use crate::PrivComId;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// Represents a player in a game
 pub struct Player {
     id: usize,
     capital: u32,
     shares: Shares,
+    #[serde(serialize_with = "crate::sorted_serde::set")]
     priv_coms: HashSet<PrivComId>,
 }
 
@@ -26,10 +28,8 @@ impl Player {
         self.priv_coms.insert(private);
     }
 
-    pub(crate) fn operate_priv_coms(&mut self) {
-        for private in &self.priv_coms {
-            self.capital += private.revenue();
-        }
+    pub(crate) fn remove_priv(&mut self, private: PrivComId) {
+        self.priv_coms.retain(|p| !p.same_as(private));
     }
 
     pub(crate) fn add_capital(&mut self, capital: u32) {
@@ -44,6 +44,11 @@ impl Player {
         &mut self.shares
     }
 
+    /// Returns the revenue of the private companies of this `Player`
+    pub fn priv_com_revenue(&self) -> u32 {
+        self.priv_coms.iter().map(|p| p.revenue()).sum()
+    }
+
     /// Returns the id of this `Player`
     pub fn id(&self) -> usize {
         self.id
@@ -58,4 +63,9 @@ impl Player {
     pub fn shares(&self) -> &Shares {
         &self.shares
     }
+
+    /// Returns the private companies of this `Player`
+    pub fn priv_coms(&self) -> &HashSet<PrivComId> {
+        &self.priv_coms
+    }
 }
@@ -1,13 +1,15 @@
 use crate::{PrivComId, PubComId, TrainId};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// Represents a public company
 pub struct PubCom {
     id: PubComId,
     capital: u32,
     stations_left: u32,
     trains: Vec<TrainId>,
+    #[serde(serialize_with = "crate::sorted_serde::set")]
     priv_coms: HashSet<PrivComId>,
     operated: bool,
 }
@@ -23,4 +25,77 @@ impl PubCom {
             operated: false,
         }
     }
+
+    pub(crate) fn add_capital(&mut self, capital: u32) {
+        self.capital += capital;
+    }
+
+    pub(crate) fn remove_capital(&mut self, capital: u32) {
+        self.capital -= capital;
+    }
+
+    pub(crate) fn add_priv(&mut self, private: PrivComId) {
+        self.priv_coms.insert(private);
+    }
+
+    pub(crate) fn remove_priv(&mut self, private: PrivComId) {
+        self.priv_coms.retain(|p| !p.same_as(private));
+    }
+
+    pub(crate) fn use_station(&mut self) {
+        self.stations_left -= 1;
+    }
+
+    pub(crate) fn add_train(&mut self, train: TrainId) {
+        self.trains.push(train);
+    }
+
+    pub(crate) fn remove_train(&mut self, train: TrainId) {
+        if let Some(i) = self.trains.iter().position(|&t| t == train) {
+            self.trains.remove(i);
+        }
+    }
+
+    pub(crate) fn rust(&mut self, train: TrainId) {
+        self.trains.retain(|&t| t != train);
+    }
+
+    pub(crate) fn set_operated(&mut self) {
+        self.operated = true;
+    }
+
+    /// Returns the id of this `PubCom`
+    pub fn id(&self) -> PubComId {
+        self.id
+    }
+
+    /// Returns the capital of this `PubCom`
+    pub fn capital(&self) -> u32 {
+        self.capital
+    }
+
+    /// Returns the number of stations left to place for this `PubCom`
+    pub fn stations_left(&self) -> u32 {
+        self.stations_left
+    }
+
+    /// Returns the trains of this `PubCom`
+    pub fn trains(&self) -> &[TrainId] {
+        &self.trains
+    }
+
+    /// Returns the private companies of this `PubCom`
+    pub fn priv_coms(&self) -> &HashSet<PrivComId> {
+        &self.priv_coms
+    }
+
+    /// Returns the revenue of the private companies of this `PubCom`
+    pub fn priv_com_revenue(&self) -> u32 {
+        self.priv_coms.iter().map(|p| p.revenue()).sum()
+    }
+
+    /// Returns whether this `PubCom` has operated
+    pub fn operated(&self) -> bool {
+        self.operated
+    }
 }
@@ -1,10 +1,13 @@
 use crate::PubComId;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
 /// Represents any compination of shares
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Shares {
+    #[serde(serialize_with = "crate::sorted_serde::map")]
     shares: HashMap<PubComId, u32>,
+    #[serde(serialize_with = "crate::sorted_serde::set")]
     presidencies: HashSet<PubComId>,
 }
 
@@ -1,35 +1,79 @@
-use crate::PubComId;
-use crate::INVALID_TOML;
-use std::collections::HashMap;
+use crate::toml_reader::{index_path, TomlReader};
+use crate::{ConfigError, PubComId};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use toml::Value;
 
-static VALUES_MISSING: &str = "values is missing";
-static VALUES_TYPEERROR: &str = "values is not of type Array of Arrays";
-static VALUE_TYPEERROR: &str = "value is not of type Integer";
-
 /// Represents the stock chart for a game
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StockChart {
-    values: Vec<Vec<(u32)>>,
+    values: Vec<Vec<u32>>,
+    par_cells: Vec<(usize, usize)>,
+    #[serde(serialize_with = "crate::sorted_serde::map")]
     tokens: HashMap<PubComId, (usize, usize, usize)>,
 }
 
 impl StockChart {
-    pub(crate) fn from_toml(s: &str) -> Self {
-        let toml = s.parse::<Value>().expect(INVALID_TOML);
+    /// Returns the `StockChart` described by the TOML in `s`, naming `file` in any `ConfigError`
+    pub fn from_toml(file: &str, s: &str) -> Result<Self, Vec<ConfigError>> {
+        let mut reader = TomlReader::new(file);
+        let stock_chart = reader
+            .parse(s)
+            .and_then(|toml| Self::read(&toml, &mut reader));
+        reader.finish(stock_chart)
+    }
+
+    fn read(toml: &Value, reader: &mut TomlReader) -> Option<Self> {
         let mut values = Vec::new();
-        let values_toml = toml.get("values").expect(VALUES_MISSING);
-        for row_toml in values_toml.as_array().expect(VALUES_TYPEERROR) {
-            let mut row = Vec::new();
-            for value in row_toml.as_array().expect(VALUES_TYPEERROR) {
-                let value = value.as_integer().expect(VALUE_TYPEERROR);
-                row.push(value as u32);
+        for (i, row_toml) in reader.array(toml, "", "values")?.iter().enumerate() {
+            let row_path = index_path("values", i);
+            let row = reader.as_array(row_toml, &row_path).map(|row_toml| {
+                let mut row = Vec::new();
+                for (j, value) in row_toml.iter().enumerate() {
+                    row.push(reader.as_unsigned(value, &index_path(&row_path, j)));
+                }
+                row
+            });
+            values.push(row.and_then(|row| row.into_iter().collect::<Option<Vec<_>>>()));
+        }
+        let values = values.into_iter().collect::<Option<Vec<_>>>()?;
+        let mut par_cells = Vec::new();
+        if let Some(par_cells_toml) = reader.opt_array(toml, "", "par_cells") {
+            for (i, cell_toml) in par_cells_toml.iter().enumerate() {
+                let path = index_path("par_cells", i);
+                par_cells.push(Self::read_par_cell(cell_toml, &path, &values, reader));
             }
-            values.push(row);
         }
-        Self {
+        Some(Self {
             values,
+            par_cells: par_cells.into_iter().collect::<Option<Vec<_>>>()?,
             tokens: HashMap::new(),
+        })
+    }
+
+    fn read_par_cell(
+        toml: &Value,
+        path: &str,
+        values: &[Vec<u32>],
+        reader: &mut TomlReader,
+    ) -> Option<(usize, usize)> {
+        let cell = reader.as_array(toml, path)?;
+        if cell.len() != 2 {
+            reader.error(path, "is not of length two".to_string());
+            return None;
+        }
+        let row = reader.as_unsigned(&cell[0], &index_path(path, 0));
+        let column = reader.as_unsigned(&cell[1], &index_path(path, 1));
+        let (row, column) = (row? as usize, column? as usize);
+        if values.get(row).is_some_and(|r| column < r.len()) {
+            Some((row, column))
+        } else {
+            reader.error(
+                path,
+                format!("is off the stock chart: [{}, {}]", row, column),
+            );
+            None
         }
     }
 
@@ -37,13 +81,112 @@ impl StockChart {
         self.tokens.insert(pub_com, self.par_to_position(par));
     }
 
+    pub(crate) fn move_right(&mut self, pub_com: PubComId) {
+        if let Some(&(row, column, _)) = self.tokens.get(&pub_com) {
+            if column + 1 < self.values[row].len() {
+                self.move_token(pub_com, row, column + 1);
+            } else if row > 0 {
+                self.move_token(pub_com, row - 1, column);
+            }
+        }
+    }
+
+    pub(crate) fn move_left(&mut self, pub_com: PubComId) {
+        if let Some(&(row, column, _)) = self.tokens.get(&pub_com) {
+            if column > 0 {
+                self.move_token(pub_com, row, column - 1);
+            } else if row + 1 < self.values.len() {
+                self.move_token(pub_com, row + 1, column);
+            }
+        }
+    }
+
+    pub(crate) fn move_up(&mut self, pub_com: PubComId) {
+        if let Some(&(row, column, _)) = self.tokens.get(&pub_com) {
+            if row > 0 {
+                self.move_token(pub_com, row - 1, column);
+            }
+        }
+    }
+
     pub(crate) fn move_down(&mut self, pub_com: PubComId, count: usize) {
-        if let Some(&(row, column, z)) = self.tokens.get(&pub_com) {
-            let new_column = usize::min(row + count, self.values[column].len() - 1);
-            self.tokens.insert(pub_com, (row, new_column, z));
+        if let Some(&(mut row, column, _)) = self.tokens.get(&pub_com) {
+            for _ in 0..count {
+                if row + 1 < self.values.len() && column < self.values[row + 1].len() {
+                    row += 1;
+                }
+            }
+            self.move_token(pub_com, row, column);
         }
     }
 
+    /// Moves the token of `pub_com` to `row`, `column`, placing it below any tokens already there
+    fn move_token(&mut self, pub_com: PubComId, row: usize, column: usize) {
+        let (old_row, old_column, old_z) = self.tokens[&pub_com];
+        if (old_row, old_column) == (row, column) {
+            return;
+        }
+        for (r, c, z) in self.tokens.values_mut() {
+            if (*r, *c) == (old_row, old_column) && *z > old_z {
+                *z -= 1;
+            }
+        }
+        let z = self.token_count_at_position(row, column);
+        self.tokens.insert(pub_com, (row, column, z));
+    }
+
+    /// Returns the position of the token of `pub_com` on this `StockChart` as row, column and
+    /// stacking order, where tokens with a lower stacking order operate first, if any
+    pub fn position(&self, pub_com: PubComId) -> Option<(usize, usize, usize)> {
+        self.tokens.get(&pub_com).copied()
+    }
+
+    /// Returns the share values of this `StockChart` by row and column
+    pub fn values(&self) -> &[Vec<u32>] {
+        &self.values
+    }
+
+    /// Returns the row and column of the cell the token of a public company with par value `par`
+    /// starts on, if any
+    ///
+    /// This is the par cell with that share value if par cells are listed, and otherwise the
+    /// lowest cell with that share value.
+    pub fn par_cell(&self, par: u32) -> Option<(usize, usize)> {
+        if self.par_cells.is_empty() {
+            self.values
+                .iter()
+                .enumerate()
+                .rev()
+                .find_map(|(row, values)| {
+                    values
+                        .iter()
+                        .position(|&v| v == par)
+                        .map(|column| (row, column))
+                })
+        } else {
+            self.par_cells
+                .iter()
+                .copied()
+                .find(|&(row, column)| self.values[row][column] == par)
+        }
+    }
+
+    /// Returns the public company in `pub_coms` to operate next according to this `StockChart`
+    pub fn next_to_operate(&self, pub_coms: &HashSet<PubComId>) -> Option<PubComId> {
+        pub_coms
+            .iter()
+            .filter_map(|&p| {
+                self.tokens.get(&p).map(|&(row, column, z)| {
+                    (
+                        p,
+                        (self.values[row][column], column, Reverse(row), Reverse(z)),
+                    )
+                })
+            })
+            .max_by_key(|&(_, key)| key)
+            .map(|(p, _)| p)
+    }
+
     /// Returns the share value of `pub_com` on this `StockChart`
     pub fn value(&self, pub_com: PubComId) -> Option<u32> {
         self.tokens
@@ -54,16 +197,71 @@ impl StockChart {
     fn token_count_at_position(&self, row: usize, column: usize) -> usize {
         self.tokens
             .values()
-            .filter(|(r, c, _)| *r == row && *c == column)
+            .filter(|&&(r, c, _)| r == row && c == column)
             .count()
     }
 
     fn par_to_position(&self, par: u32) -> (usize, usize, usize) {
-        match par {
-            70 => (4, 2, self.token_count_at_position(4, 2)),
-            80 => (3, 3, self.token_count_at_position(3, 3)),
-            95 => (2, 4, self.token_count_at_position(2, 4)),
-            _ => unreachable!(),
-        }
+        let (row, column) = self.par_cell(par).unwrap();
+        (row, column, self.token_count_at_position(row, column))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StockChart;
+    use crate::PubComId;
+
+    fn stock_chart() -> StockChart {
+        let toml = "values = [[80, 90, 100], [70, 80], [60]]\npar_cells = [[1, 1]]";
+        StockChart::from_toml("stock_chart.toml", toml).unwrap()
+    }
+
+    #[test]
+    fn tokens_move_right_and_up_at_the_edge() {
+        let mut stock_chart = stock_chart();
+        stock_chart.add_token(PubComId::PRR, 80);
+        assert_eq!(stock_chart.position(PubComId::PRR), Some((1, 1, 0)));
+        stock_chart.move_right(PubComId::PRR);
+        assert_eq!(stock_chart.position(PubComId::PRR), Some((0, 1, 0)));
+        stock_chart.move_right(PubComId::PRR);
+        stock_chart.move_right(PubComId::PRR);
+        assert_eq!(stock_chart.value(PubComId::PRR), Some(100));
+    }
+
+    #[test]
+    fn tokens_move_left_and_down_at_the_edge() {
+        let mut stock_chart = stock_chart();
+        stock_chart.add_token(PubComId::PRR, 80);
+        stock_chart.move_left(PubComId::PRR);
+        assert_eq!(stock_chart.value(PubComId::PRR), Some(70));
+        stock_chart.move_left(PubComId::PRR);
+        assert_eq!(stock_chart.position(PubComId::PRR), Some((2, 0, 0)));
+        stock_chart.move_left(PubComId::PRR);
+        assert_eq!(stock_chart.value(PubComId::PRR), Some(60));
+    }
+
+    #[test]
+    fn tokens_move_down_only_onto_the_chart() {
+        let mut stock_chart = stock_chart();
+        stock_chart.add_token(PubComId::PRR, 80);
+        stock_chart.move_down(PubComId::PRR, 3);
+        assert_eq!(stock_chart.position(PubComId::PRR), Some((1, 1, 0)));
+        stock_chart.move_left(PubComId::PRR);
+        stock_chart.move_down(PubComId::PRR, 3);
+        assert_eq!(stock_chart.value(PubComId::PRR), Some(60));
+    }
+
+    #[test]
+    fn moved_tokens_stack_below_tokens_already_there() {
+        let mut stock_chart = stock_chart();
+        stock_chart.add_token(PubComId::PRR, 80);
+        stock_chart.add_token(PubComId::LV, 80);
+        stock_chart.add_token(PubComId::BAndO, 80);
+        stock_chart.move_right(PubComId::PRR);
+        assert_eq!(stock_chart.position(PubComId::LV), Some((1, 1, 0)));
+        assert_eq!(stock_chart.position(PubComId::BAndO), Some((1, 1, 1)));
+        stock_chart.move_right(PubComId::LV);
+        assert_eq!(stock_chart.position(PubComId::LV), Some((0, 1, 1)));
     }
 }
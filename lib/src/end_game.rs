@@ -0,0 +1,192 @@
+use crate::{Game, PubComId, RoundId};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// Represents the reason a game ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EndReason {
+    /// The bank broke and the set of operating rounds it broke in was finished
+    BankBroken,
+    /// The president of `pub_com` could not fund a train for it
+    Bankruptcy {
+        /// The public company
+        pub_com: PubComId,
+        /// The bankrupt president
+        player: usize,
+    },
+}
+
+/// Represents the final position of a player
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Standing {
+    rank: usize,
+    player: usize,
+    capital: u32,
+    share_value: u32,
+}
+
+impl Standing {
+    /// Returns the rank of this `Standing`, starting at 1 and shared by players with equal totals
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    /// Returns the player of this `Standing`
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    /// Returns the capital of the player of this `Standing`
+    pub fn capital(&self) -> u32 {
+        self.capital
+    }
+
+    /// Returns the value of the shares of the player of this `Standing`
+    pub fn share_value(&self) -> u32 {
+        self.share_value
+    }
+
+    /// Returns the total of the capital and share value of the player of this `Standing`
+    pub fn total(&self) -> u32 {
+        self.capital + self.share_value
+    }
+}
+
+impl Game {
+    /// Returns the reason this `Game` ended, if it is over
+    pub fn end_reason(&self) -> Option<EndReason> {
+        if let RoundId::GameOver(reason) = self.round {
+            Some(reason)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether this `Game` is over
+    pub fn is_over(&self) -> bool {
+        self.end_reason().is_some()
+    }
+
+    /// Returns the players of this `Game` ranked by capital plus share value, with the share value
+    /// taken from the stock chart
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<_> = self
+            .players
+            .iter()
+            .map(|player| Standing {
+                rank: 0,
+                player: player.id(),
+                capital: player.capital(),
+                share_value: PubComId::values()
+                    .map(|p| player.shares().count(p) * self.stock_chart.value(p).unwrap_or(0))
+                    .sum(),
+            })
+            .collect();
+        standings.sort_by_key(|s| (Reverse(s.total()), s.player));
+        for i in 0..standings.len() {
+            standings[i].rank = if i > 0 && standings[i].total() == standings[i - 1].total() {
+                standings[i - 1].rank
+            } else {
+                i + 1
+            };
+        }
+        standings
+    }
+
+    pub(crate) fn end_game(&mut self, reason: EndReason) {
+        self.round = RoundId::GameOver(reason);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EndReason;
+    use crate::game::tests;
+    use crate::{Action, ActionError, Game, GameConfig, PubComId};
+
+    fn operating_round_game(bank_size: u32) -> Game {
+        let config = GameConfig::new().bank_size(bank_size);
+        let mut game = Game::with_config(4, &config).unwrap();
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.enter_operating_round(0);
+        game
+    }
+
+    fn operate(game: &mut Game) {
+        for _ in 0..3 {
+            game.pass().unwrap();
+        }
+        game.buy_train().unwrap();
+        game.pass().unwrap();
+    }
+
+    #[test]
+    fn game_continues_while_bank_holds_capital() {
+        let mut game = operating_round_game(8000);
+        assert_eq!(game.bank().capital(), 4800);
+        operate(&mut game);
+        assert!(!game.bank().is_broken());
+        assert!(!game.is_over());
+    }
+
+    #[test]
+    fn game_ends_after_operating_rounds_in_which_bank_broke() {
+        let mut game = operating_round_game(3200);
+        assert!(game.bank().is_broken());
+        assert!(!game.is_over());
+        operate(&mut game);
+        assert_eq!(game.end_reason(), Some(EndReason::BankBroken));
+        assert_eq!(game.check_pass(), Err(ActionError::GameOver));
+        assert_eq!(game.check_action(Action::Pass), Err(ActionError::GameOver));
+    }
+
+    #[test]
+    fn declare_bankruptcy() {
+        let mut game = operating_round_game(8000);
+        for _ in 0..3 {
+            game.pass().unwrap();
+        }
+        assert_eq!(
+            game.check_declare_bankruptcy(),
+            Err(ActionError::NotBankrupt)
+        );
+        let capital = game.pub_coms[&PubComId::PRR].capital();
+        game.pub_coms
+            .get_mut(&PubComId::PRR)
+            .unwrap()
+            .remove_capital(capital);
+        game.players[1].remove_capital(600);
+        assert_eq!(game.declare_bankruptcy(), Ok(()));
+        assert_eq!(
+            game.end_reason(),
+            Some(EndReason::Bankruptcy {
+                pub_com: PubComId::PRR,
+                player: 1
+            })
+        );
+    }
+
+    #[test]
+    fn declare_bankruptcy_outside_buy_trains_is_rejected() {
+        let game = tests::game(4);
+        assert_eq!(
+            game.check_declare_bankruptcy(),
+            Err(ActionError::WrongRound)
+        );
+    }
+
+    #[test]
+    fn standings_share_ranks_on_equal_totals() {
+        let mut game = tests::game(3);
+        tests::par(&mut game, PubComId::PRR, 80, 1);
+        game.players[0].remove_capital(40);
+        game.players[1].remove_capital(200);
+        let standings = game.standings();
+        let ranks: Vec<_> = standings
+            .iter()
+            .map(|s| (s.rank(), s.player(), s.total()))
+            .collect();
+        assert_eq!(ranks, [(1, 2, 800), (2, 0, 760), (2, 1, 760)]);
+        assert_eq!(standings[2].share_value(), 160);
+    }
+}
@@ -0,0 +1,286 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Represents the reason an action was rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ActionError {
+    /// The action is not available in the current round
+    WrongRound,
+    /// The private auction has no private company left for sale
+    AuctionOver,
+    /// The current private company is being auctioned
+    AuctionInProgress,
+    /// The current player is not bidding on the current private company
+    NotInAuction,
+    /// The current player already holds the highest bid
+    AlreadyHighestBidder,
+    /// The current player has already bid on the private company
+    AlreadyBid,
+    /// The bid is too low compared to the highest bid
+    BidTooLow,
+    /// The current private company is free and must be bought
+    MustBuyPrivate,
+    /// The buyer can not afford the action
+    InsufficientCapital,
+    /// The current player has already performed an action this turn
+    ActionAlreadyPerformed,
+    /// The current player has not performed an action this turn
+    NoActionPerformed,
+    /// The current player has sold shares of the public company this stock round
+    SoldThisRound,
+    /// The current player would exceed the certificate limit
+    CertificateLimit,
+    /// The current player would hold more than 60% of the public company
+    HoldingLimit,
+    /// The public company has no par value
+    NotParred,
+    /// The public company already has a par value
+    AlreadyParred,
+    /// The par value is not on the par track
+    InvalidParValue,
+    /// There are no shares of the public company available
+    NoSharesAvailable,
+    /// Selling shares is not allowed in this stock round
+    SellingNotAllowed,
+    /// The current player does not own enough shares
+    NotEnoughShares,
+    /// The bank pool would hold more than 50% of the public company
+    PoolShareLimit,
+    /// The presidency can not be dumped since no other player owns 20%
+    PresidencyDump,
+    /// The action is not available in the current operating phase
+    WrongOperatingPhase,
+    /// There is no hex at the given position
+    InvalidHex,
+    /// The rotation is not between 0 and 5
+    InvalidRotation,
+    /// There are no tiles with the given id left
+    TileUnavailable,
+    /// Tiles of the given color are not available in the current phase
+    ColorUnavailable,
+    /// The tile is not a valid upgrade of the hex
+    InvalidUpgrade,
+    /// The tile does not connect to the track of the public company
+    NotConnected,
+    /// The tile has track running off the map or into a blank edge of a red or gray hex
+    TrackBlocked,
+    /// The public company has no stations left to place
+    NoStationsLeft,
+    /// There is no city with the given index in the hex
+    InvalidCity,
+    /// The city has no free station spots
+    CityFull,
+    /// The public company already has a station in the city
+    AlreadyStationed,
+    /// The city is not reachable from the stations of the public company
+    CityNotReachable,
+    /// The free station spots of the city are reserved for the home stations of public companies
+    /// that have not floated
+    CityReserved,
+    /// The public company owns trains and must run them
+    MustRunTrains,
+    /// There are no trains available to buy
+    NoTrainsAvailable,
+    /// The train is not available from the seller
+    TrainUnavailable,
+    /// The public company would exceed the train limit
+    TrainLimit,
+    /// The public company owns no trains and must buy one
+    MustBuyTrain,
+    /// The selling public company does not have the same president
+    NotSamePresident,
+    /// The price is not valid
+    InvalidPrice,
+    /// The acting player is not the current player
+    NotYourTurn,
+    /// There is no action that may be undone
+    NothingToUndo,
+    /// There is no undone action to redo
+    NothingToRedo,
+    /// The private company is not for sale
+    PrivateUnavailable,
+    /// The game is over
+    GameOver,
+    /// The public company can buy a train and is not bankrupt
+    NotBankrupt,
+    /// The hex is reserved by a private company owned by a player
+    HexBlocked,
+    /// The public company does not own the private company
+    PrivateNotOwned,
+    /// The private company has no such ability
+    NoAbility,
+    /// The hex is not reserved by the private company
+    HexNotReserved,
+    /// The current player is not the president of the public company
+    NotPresident,
+    /// The action is not available in the current phase
+    WrongPhase,
+}
+
+impl Display for ActionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ActionError::WrongRound => write!(f, "action is not allowed in this round"),
+            ActionError::AuctionOver => write!(f, "private auction is over"),
+            ActionError::AuctionInProgress => write!(f, "current private company is auctioned"),
+            ActionError::NotInAuction => write!(f, "player is not in the auction"),
+            ActionError::AlreadyHighestBidder => write!(f, "player already has the highest bid"),
+            ActionError::AlreadyBid => write!(f, "player has already bid on private company"),
+            ActionError::BidTooLow => write!(f, "bid is too low"),
+            ActionError::MustBuyPrivate => write!(f, "private company must be bought"),
+            ActionError::InsufficientCapital => write!(f, "capital is insufficient"),
+            ActionError::ActionAlreadyPerformed => write!(f, "action is already performed"),
+            ActionError::NoActionPerformed => write!(f, "no action is performed"),
+            ActionError::SoldThisRound => write!(f, "shares were sold this round"),
+            ActionError::CertificateLimit => write!(f, "certificate limit is exceeded"),
+            ActionError::HoldingLimit => write!(f, "holding limit is exceeded"),
+            ActionError::NotParred => write!(f, "public company has no par value"),
+            ActionError::AlreadyParred => write!(f, "public company already has a par value"),
+            ActionError::InvalidParValue => write!(f, "par value is invalid"),
+            ActionError::NoSharesAvailable => write!(f, "no shares are available"),
+            ActionError::SellingNotAllowed => write!(f, "selling is not allowed"),
+            ActionError::NotEnoughShares => write!(f, "not enough shares are owned"),
+            ActionError::PoolShareLimit => write!(f, "pool share limit is exceeded"),
+            ActionError::PresidencyDump => write!(f, "presidency can not be dumped"),
+            ActionError::WrongOperatingPhase => {
+                write!(f, "action is not allowed in this operating phase")
+            }
+            ActionError::InvalidHex => write!(f, "hex does not exist"),
+            ActionError::InvalidRotation => write!(f, "rotation is invalid"),
+            ActionError::TileUnavailable => write!(f, "tile is not available"),
+            ActionError::ColorUnavailable => write!(f, "tile color is not available"),
+            ActionError::InvalidUpgrade => write!(f, "tile is not a valid upgrade"),
+            ActionError::NotConnected => write!(f, "tile is not connected"),
+            ActionError::TrackBlocked => write!(f, "track is blocked"),
+            ActionError::NoStationsLeft => write!(f, "no stations are left"),
+            ActionError::InvalidCity => write!(f, "city does not exist"),
+            ActionError::CityFull => write!(f, "city is full"),
+            ActionError::AlreadyStationed => write!(f, "public company already has a station"),
+            ActionError::CityNotReachable => write!(f, "city is not reachable"),
+            ActionError::CityReserved => write!(f, "city is reserved for a home station"),
+            ActionError::MustRunTrains => write!(f, "trains must be run"),
+            ActionError::NoTrainsAvailable => write!(f, "no trains are available"),
+            ActionError::TrainUnavailable => write!(f, "train is not available"),
+            ActionError::TrainLimit => write!(f, "train limit is exceeded"),
+            ActionError::MustBuyTrain => write!(f, "a train must be bought"),
+            ActionError::NotSamePresident => {
+                write!(f, "public companies have different presidents")
+            }
+            ActionError::InvalidPrice => write!(f, "price is invalid"),
+            ActionError::NotYourTurn => write!(f, "it is not the turn of this player"),
+            ActionError::NothingToUndo => write!(f, "there is nothing to undo"),
+            ActionError::NothingToRedo => write!(f, "there is nothing to redo"),
+            ActionError::PrivateUnavailable => write!(f, "private company is not for sale"),
+            ActionError::GameOver => write!(f, "game is over"),
+            ActionError::NotBankrupt => write!(f, "public company can buy a train"),
+            ActionError::HexBlocked => write!(f, "hex is reserved by a private company"),
+            ActionError::PrivateNotOwned => write!(f, "private company is not owned"),
+            ActionError::NoAbility => write!(f, "private company has no such ability"),
+            ActionError::HexNotReserved => {
+                write!(f, "hex is not reserved by the private company")
+            }
+            ActionError::NotPresident => write!(f, "player is not the president"),
+            ActionError::WrongPhase => write!(f, "action is not allowed in this phase"),
+        }
+    }
+}
+
+impl Error for ActionError {}
+
+/// Represents a problem found while loading a configuration file
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConfigError {
+    file: String,
+    path: String,
+    problem: String,
+}
+
+impl ConfigError {
+    pub(crate) fn new(file: &str, path: &str, problem: String) -> Self {
+        Self {
+            file: file.to_string(),
+            path: path.to_string(),
+            problem,
+        }
+    }
+
+    /// Returns the name of the file this `ConfigError` was found in
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the TOML path this `ConfigError` was found at, e.g. `hexes[17].rails[0].city.spots`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the problem of this `ConfigError`
+    pub fn problem(&self) -> &str {
+        &self.problem
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.path.is_empty() {
+            write!(f, "{}: {}", self.file, self.problem)
+        } else {
+            write!(f, "{}: {} {}", self.file, self.path, self.problem)
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Represents the reason a snapshot could not be loaded
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SnapshotError {
+    /// The snapshot has a version this library can not load
+    UnsupportedVersion(u32),
+    /// The snapshot is malformed
+    Malformed(String),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "snapshot version {} is not supported", version)
+            }
+            SnapshotError::Malformed(problem) => write!(f, "snapshot is malformed: {}", problem),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+/// Represents the reason a log could not be replayed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The starting configuration could not be loaded
+    Config(Vec<ConfigError>),
+    /// The action at `index` in the log was rejected
+    Action {
+        /// The index of the action in the log
+        index: usize,
+        /// The reason the action was rejected
+        error: ActionError,
+    },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ReplayError::Config(errors) => {
+                write!(f, "configuration is invalid")?;
+                for error in errors {
+                    write!(f, "\n{}", error)?;
+                }
+                Ok(())
+            }
+            ReplayError::Action { index, error } => write!(f, "action {} failed: {}", index, error),
+        }
+    }
+}
+
+impl Error for ReplayError {}
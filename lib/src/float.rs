@@ -0,0 +1,196 @@
+use crate::economy::PubCom;
+use crate::{Game, PubComId, RoundId};
+use serde::{Deserialize, Serialize};
+
+/// Represents how a public company is paid for the shares sold from the IPO
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Capitalization {
+    /// The public company is paid ten times its par value by the bank when it floats, while the
+    /// shares sold from the IPO are paid to the bank
+    #[default]
+    Full,
+    /// The public company is paid its par value for each share sold from the IPO, those sold
+    /// before it floats being paid to it by the bank when it floats
+    Incremental,
+}
+
+/// Represents who is paid for the presidency of a public company under incremental
+/// capitalization
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PresidencySource {
+    /// The presidency is sold from the IPO and paid to the public company like any other share
+    #[default]
+    Ipo,
+    /// The presidency is sold by the bank, which keeps what is paid for it
+    Bank,
+}
+
+impl Game {
+    /// Returns the percentage of the shares of a public company that must be sold before it floats
+    pub fn float_percent(&self) -> u32 {
+        self.float_percent
+    }
+
+    /// Returns how the public companies are paid for the shares sold from the IPO
+    pub fn capitalization(&self) -> Capitalization {
+        self.capitalization
+    }
+
+    /// Returns who is paid for the presidencies of the public companies under incremental
+    /// capitalization
+    pub fn presidency_source(&self) -> PresidencySource {
+        self.presidency_source
+    }
+
+    /// Returns the percentage of the shares of `pub_com` that have been sold, counting the
+    /// presidency as two shares
+    pub fn sold_percent(&self, pub_com: PubComId) -> u32 {
+        let presidency = if self.ipo.contains_presidency(pub_com) {
+            2
+        } else {
+            0
+        };
+        (10 - self.ipo.count(pub_com) - presidency) * 10
+    }
+
+    /// Floats `pub_com` if it has a par value and enough of its shares have been sold, paying it
+    /// its starting capital and placing its home station
+    ///
+    /// Does nothing if `pub_com` has already floated, so that its home station is placed exactly
+    /// once.
+    pub(crate) fn float_pub_com(&mut self, pub_com: PubComId) {
+        let par = match self.par_track.value(pub_com) {
+            Some(par) => par,
+            None => return,
+        };
+        if self.pub_coms.contains_key(&pub_com) || self.sold_percent(pub_com) < self.float_percent {
+            return;
+        }
+        let capital = match self.capitalization {
+            Capitalization::Full => 10 * par,
+            Capitalization::Incremental => par * self.ipo_shares_sold(pub_com),
+        };
+        self.bank.withdraw(capital);
+        let mut floated = PubCom::new(pub_com, capital);
+        if self.map.place_home_station(pub_com) {
+            floated.use_station();
+        }
+        self.pub_coms.insert(pub_com, floated);
+    }
+
+    /// Pays `par` for a share of `pub_com` sold from the IPO to the bank, or to `pub_com` if it
+    /// has floated with incremental capitalization
+    pub(crate) fn pay_ipo_share(&mut self, pub_com: PubComId, par: u32) {
+        match self.pub_coms.get_mut(&pub_com) {
+            Some(floated) if self.capitalization == Capitalization::Incremental => {
+                floated.add_capital(par);
+            }
+            _ => self.bank.deposit(par),
+        }
+    }
+
+    /// Returns the number of shares of `pub_com` sold from the IPO that it is paid for under
+    /// incremental capitalization
+    ///
+    /// Once the private auction is over, the presidency of the B&O and a share of the CV public
+    /// company have been given away with private companies, so nobody paid par for them.
+    fn ipo_shares_sold(&self, pub_com: PubComId) -> u32 {
+        let sold = self.sold_percent(pub_com) / 10;
+        let auction_over = !matches!(self.round, RoundId::PrivAuction(_));
+        let presidency = if !self.ipo.contains_presidency(pub_com)
+            && (self.presidency_source == PresidencySource::Bank
+                || auction_over && pub_com == PubComId::BAndO)
+        {
+            2
+        } else {
+            0
+        };
+        let granted = if auction_over && pub_com == self.cv_pub_com {
+            1
+        } else {
+            0
+        };
+        sold.saturating_sub(presidency + granted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rounds::StockRound;
+    use crate::{Capitalization, Game, GameConfig, PresidencySource, PrivComId, PubComId, RoundId};
+
+    fn stock_round_game(config: GameConfig) -> Game {
+        let config = config.cv_pub_com(PubComId::SRR);
+        let mut game = Game::with_config(4, &config).unwrap();
+        game.round = RoundId::StockRound(StockRound::new(false));
+        game
+    }
+
+    /// Buys the presidency of `pub_com` at par 80 and then `count` shares from the IPO
+    fn sell_shares(game: &mut Game, pub_com: PubComId, count: u32) {
+        game.buy_presidency(pub_com, 80).unwrap();
+        for _ in 0..count {
+            game.buy_ipo_share(pub_com).unwrap();
+        }
+    }
+
+    #[test]
+    fn full_capitalization() {
+        let mut game = stock_round_game(GameConfig::new());
+        sell_shares(&mut game, PubComId::PRR, 3);
+        assert!(!game.pub_coms.contains_key(&PubComId::PRR));
+        game.buy_ipo_share(PubComId::PRR).unwrap();
+        assert_eq!(game.sold_percent(PubComId::PRR), 60);
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 800);
+        assert_eq!(game.map.stations(PubComId::PRR), &[(5, 0, 0)]);
+        game.buy_ipo_share(PubComId::PRR).unwrap();
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 800);
+        assert_eq!(game.map.stations(PubComId::PRR).len(), 1);
+        assert!(game.capital_conserved());
+    }
+
+    #[test]
+    fn incremental_capitalization() {
+        let config = GameConfig::new().capitalization(Capitalization::Incremental);
+        let mut game = stock_round_game(config);
+        sell_shares(&mut game, PubComId::PRR, 4);
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 480);
+        game.buy_ipo_share(PubComId::PRR).unwrap();
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 560);
+        assert!(game.capital_conserved());
+    }
+
+    #[test]
+    fn bank_keeps_presidency_payment() {
+        let config = GameConfig::new()
+            .capitalization(Capitalization::Incremental)
+            .presidency_source(PresidencySource::Bank);
+        let mut game = stock_round_game(config);
+        sell_shares(&mut game, PubComId::PRR, 4);
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 320);
+        assert!(game.capital_conserved());
+    }
+
+    #[test]
+    fn cv_share_is_not_paid_for() {
+        let config = GameConfig::new().capitalization(Capitalization::Incremental);
+        let mut game = stock_round_game(config);
+        game.sell_priv(3, PrivComId::CV, 200);
+        sell_shares(&mut game, PubComId::SRR, 3);
+        assert_eq!(game.sold_percent(PubComId::SRR), 60);
+        assert_eq!(game.pub_coms[&PubComId::SRR].capital(), 400);
+        assert!(game.capital_conserved());
+    }
+
+    #[test]
+    fn float_percent() {
+        let config = GameConfig::new().float_percent(20);
+        let mut game = stock_round_game(config);
+        sell_shares(&mut game, PubComId::PRR, 0);
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 800);
+        for &float_percent in &[0, 15, 110] {
+            let config = GameConfig::new().float_percent(float_percent);
+            assert!(Game::with_config(4, &config).is_err());
+        }
+    }
+}
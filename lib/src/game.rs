@@ -1,14 +1,17 @@
-use crate::economy::{ParTrack, Player, PubCom, Shares, StockChart};
+use crate::economy::{Bank, ParTrack, Player, PubCom, Shares, StockChart};
 use crate::geography::{Map, TileSet};
 use crate::rounds::{OperatingRound, PrivAuction, StockRound};
-use crate::{PhaseId, PrivComId, PubComId, RoundId, TrainSet};
+use crate::{
+    Action, ActionError, Capitalization, ConfigError, GameConfig, OperatingPhaseId, PhaseId,
+    PresidencySource, PrivComId, PubComId, RoundId, TrainSet, UndoPolicy,
+};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::{fs, u32};
 
-static ACTION_FORBIDDEN: &str = "action is forbidden";
+static STARTING_CAPITAL: u32 = 2400;
 
 /// Represents a game
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Game {
     pub(crate) round: RoundId,
     pub(crate) phase: PhaseId,
@@ -16,6 +19,7 @@ pub struct Game {
     pub(crate) current_player: usize,
     pub(crate) priority_player: usize,
     pub(crate) passes: usize,
+    #[serde(serialize_with = "crate::sorted_serde::map")]
     pub(crate) pub_coms: HashMap<PubComId, PubCom>,
     pub(crate) map: Map,
     pub(crate) tile_set: TileSet,
@@ -24,19 +28,37 @@ pub struct Game {
     pub(crate) par_track: ParTrack,
     pub(crate) ipo: Shares,
     pub(crate) pool: Shares,
+    pub(crate) bank: Bank,
+    pub(crate) cv_pub_com: PubComId,
+    pub(crate) float_percent: u32,
+    pub(crate) capitalization: Capitalization,
+    pub(crate) presidency_source: PresidencySource,
+    pub(crate) log: Vec<(usize, Action)>,
+    pub(crate) redo_log: Vec<(usize, Action)>,
+    pub(crate) undo_policy: UndoPolicy,
+    pub(crate) start: Option<Box<Game>>,
 }
 
 impl Game {
-    /// Returns a game
-    pub fn new(player_count: usize) -> Self {
-        if player_count < 2 || player_count > 6 {
-            panic!("player_count out of bounds");
+    /// Returns a game for `player_count` players using `config`, or the errors found if
+    /// `player_count` is not between 2 and 6 or `config` is invalid
+    pub fn with_config(player_count: usize, config: &GameConfig) -> Result<Self, Vec<ConfigError>> {
+        if !(2..=6).contains(&player_count) {
+            return Err(vec![ConfigError::new(
+                "GameConfig",
+                "player_count",
+                format!("is not between 2 and 6: {}", player_count),
+            )]);
         }
+        let config = config.load()?;
+        let mut bank = Bank::new(config.bank_size);
         let mut players = Vec::with_capacity(player_count);
         for id in 0..player_count {
-            players.push(Player::new(id, 2400 / player_count as u32));
+            let capital = STARTING_CAPITAL / player_count as u32;
+            bank.withdraw(capital);
+            players.push(Player::new(id, capital));
         }
-        Self {
+        let mut game = Self {
             round: RoundId::PrivAuction(PrivAuction::new(player_count)),
             phase: PhaseId::Phase2,
             players,
@@ -44,74 +66,140 @@ impl Game {
             priority_player: 0,
             passes: 0,
             pub_coms: HashMap::new(),
-            map: Map::from_toml(&read_toml_file("map")),
-            tile_set: TileSet::from_toml(&read_toml_file("tile_set")),
-            train_set: TrainSet::from_toml(&read_toml_file("train_set")),
-            stock_chart: StockChart::from_toml(&read_toml_file("stock_chart")),
-            par_track: ParTrack::from_toml(&read_toml_file("par_track")),
+            map: config.map,
+            tile_set: config.tile_set,
+            train_set: config.train_set,
+            stock_chart: config.stock_chart,
+            par_track: config.par_track,
             ipo: Shares::ipo_shares(),
             pool: Shares::empty_shares(),
-        }
+            bank,
+            cv_pub_com: config.cv_pub_com,
+            float_percent: config.float_percent,
+            capitalization: config.capitalization,
+            presidency_source: config.presidency_source,
+            log: Vec::new(),
+            redo_log: Vec::new(),
+            undo_policy: UndoPolicy::default(),
+            start: None,
+        };
+        game.start = Some(Box::new(game.clone()));
+        Ok(game)
     }
 
     /// Returns whether passing is allowed
     pub fn pass_allowed(&self) -> bool {
+        self.check_pass().is_ok()
+    }
+
+    /// Returns `Ok` if passing is allowed, otherwise the reason it is not
+    pub fn check_pass(&self) -> Result<(), ActionError> {
         match &self.round {
             RoundId::StockRound(stock_round) => {
-                !stock_round.action_performed()
-                    && self.certificate_count(&self.players[self.current_player])
-                        <= self.certificate_limit()
+                if stock_round.action_performed() {
+                    Err(ActionError::ActionAlreadyPerformed)
+                } else if self.certificate_count(&self.players[self.current_player])
+                    > self.certificate_limit()
+                {
+                    Err(ActionError::CertificateLimit)
+                } else {
+                    Ok(())
+                }
+            }
+            RoundId::PrivAuction(priv_auction) => {
+                let current_priv = priv_auction.current().ok_or(ActionError::AuctionOver)?;
+                if current_priv.cost() == 0 {
+                    Err(ActionError::MustBuyPrivate)
+                } else if current_priv.cost() != priv_auction.max_bid(current_priv)
+                    && !priv_auction
+                        .bids(&self.players[self.current_player])
+                        .contains_key(&current_priv)
+                {
+                    Err(ActionError::NotInAuction)
+                } else {
+                    Ok(())
+                }
+            }
+            RoundId::OperatingRound(operating_round) => {
+                let pub_com = &self.pub_coms[&operating_round.current().unwrap()];
+                match operating_round.phase() {
+                    OperatingPhaseId::DistributeEarnings if !pub_com.trains().is_empty() => {
+                        Err(ActionError::MustRunTrains)
+                    }
+                    OperatingPhaseId::BuyTrains if pub_com.trains().is_empty() => {
+                        Err(ActionError::MustBuyTrain)
+                    }
+                    _ => Ok(()),
+                }
             }
-            RoundId::PrivAuction(priv_auction) => priv_auction
-                .current_if_pass_allowed(&self.players[self.current_player])
-                .is_some(),
-            _ => false,
+            RoundId::GameOver(_) => Err(ActionError::GameOver),
         }
     }
 
     /// Passes
-    pub fn pass(&mut self) {
-        if !self.pass_allowed() {
-            panic!(ACTION_FORBIDDEN);
-        }
+    pub fn pass(&mut self) -> Result<(), ActionError> {
+        self.check_pass()?;
         match &mut self.round {
             RoundId::StockRound(_) => {
                 self.passes += 1;
                 if self.passes == self.players.len() {
-                    self.passes = 0;
-                    self.enter_first_operating_round();
+                    self.finish_stock_round();
+                    return Ok(());
                 }
             }
             RoundId::PrivAuction(priv_auction) => {
                 let current_player = &self.players[self.current_player];
-                if let Some(current_priv) = priv_auction.current_if_pass_allowed(&current_player) {
-                    if priv_auction.max_bid(current_priv) != current_priv.cost() {
-                        self.passes = 0;
-                        priv_auction.remove_bid(&current_player, current_priv);
-                        if let Some((player, amount)) = priv_auction.only_bid(current_priv) {
-                            priv_auction.advance_current();
-                            self.players[player].buy_priv(current_priv, amount);
-                            if priv_auction.current().is_none() {
-                                self.enter_first_stock_round();
-                                return;
-                            }
+                let current_priv = priv_auction.current().unwrap();
+                if priv_auction.max_bid(current_priv) != current_priv.cost() {
+                    self.passes = 0;
+                    priv_auction.remove_bid(current_player, current_priv);
+                    if let Some((player, amount)) = priv_auction.only_bid(current_priv) {
+                        priv_auction.advance_current();
+                        let auction_over = priv_auction.current().is_none();
+                        self.sell_priv(player, current_priv, amount);
+                        if auction_over {
+                            self.enter_first_stock_round();
+                            return Ok(());
                         }
-                    } else {
-                        self.passes += 1;
-                        if self.passes == self.players.len() {
-                            self.passes = 0;
-                            if let PrivComId::DAndR(cost) = current_priv {
-                                priv_auction.reduce_d_and_r_price(cost);
-                            } else {
-                                self.operate_priv_coms();
-                            }
+                    }
+                } else {
+                    self.passes += 1;
+                    if self.passes == self.players.len() {
+                        self.passes = 0;
+                        if let PrivComId::DAndR(cost) = current_priv {
+                            priv_auction.reduce_d_and_r_price(cost);
+                        } else {
+                            self.operate_priv_coms();
                         }
                     }
                 }
             }
-            _ => unreachable!(),
+            RoundId::OperatingRound(operating_round) => {
+                if operating_round.phase() == OperatingPhaseId::DistributeEarnings {
+                    self.distribute_earnings(0, false);
+                }
+                self.advance_operating_phase();
+                return Ok(());
+            }
+            RoundId::GameOver(_) => unreachable!(),
         }
         self.advance_current_player();
+        Ok(())
+    }
+
+    /// Returns the current round of this `Game`
+    pub fn round(&self) -> &RoundId {
+        &self.round
+    }
+
+    /// Returns the current phase of this `Game`
+    pub fn phase(&self) -> PhaseId {
+        self.phase
+    }
+
+    /// Returns the players of this `Game`
+    pub fn players(&self) -> &[Player] {
+        &self.players
     }
 
     /// Returns the current player of this `Game`
@@ -119,6 +207,73 @@ impl Game {
         self.current_player
     }
 
+    /// Returns `Ok` if `player` is the current player, otherwise `ActionError::NotYourTurn`
+    pub fn check_current_player(&self, player: usize) -> Result<(), ActionError> {
+        if player == self.current_player {
+            Ok(())
+        } else {
+            Err(ActionError::NotYourTurn)
+        }
+    }
+
+    /// Returns the priority player of this `Game`
+    pub fn priority_player(&self) -> usize {
+        self.priority_player
+    }
+
+    /// Returns the floated public company `pub_com` of this `Game`, if any
+    pub fn pub_com(&self, pub_com: PubComId) -> Option<&PubCom> {
+        self.pub_coms.get(&pub_com)
+    }
+
+    /// Returns the map of this `Game`
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    /// Returns the tile set of this `Game`
+    pub fn tile_set(&self) -> &TileSet {
+        &self.tile_set
+    }
+
+    /// Returns the train set of this `Game`
+    pub fn train_set(&self) -> &TrainSet {
+        &self.train_set
+    }
+
+    /// Returns the stock chart of this `Game`
+    pub fn stock_chart(&self) -> &StockChart {
+        &self.stock_chart
+    }
+
+    /// Returns the par track of this `Game`
+    pub fn par_track(&self) -> &ParTrack {
+        &self.par_track
+    }
+
+    /// Returns the bank of this `Game`
+    pub fn bank(&self) -> &Bank {
+        &self.bank
+    }
+
+    /// Returns whether the capital of the bank, players and public companies of this `Game` adds
+    /// up to the size of the bank plus its overdraft, as no payment creates or destroys capital
+    pub fn capital_conserved(&self) -> bool {
+        let players: u32 = self.players.iter().map(|p| p.capital()).sum();
+        let pub_coms: u32 = self.pub_coms.values().map(|p| p.capital()).sum();
+        self.bank.capital() + players + pub_coms == self.bank.size() + self.bank.overdraft()
+    }
+
+    /// Returns the shares in the IPO of this `Game`
+    pub fn ipo(&self) -> &Shares {
+        &self.ipo
+    }
+
+    /// Returns the shares in the bank pool of this `Game`
+    pub fn pool(&self) -> &Shares {
+        &self.pool
+    }
+
     /// Returns the president of `pub_com` in this `Game`, if any
     pub fn president(&self, pub_com: PubComId) -> Option<usize> {
         for player in &self.players {
@@ -128,6 +283,7 @@ impl Game {
         }
         None
     }
+
     /// Returns the certificate limit of this `Game`
     pub fn certificate_limit(&self) -> u32 {
         match self.players.len() {
@@ -178,20 +334,35 @@ impl Game {
         self.current_player = self.priority_player;
     }
 
-    pub(crate) fn enter_first_operating_round(&mut self) {
-        self.round = RoundId::OperatingRound(OperatingRound::new(
-            self.phase.operating_round_count() - 1,
-            PubComId::values()
-                .filter(|&p| self.stock_chart.value(p).is_some())
-                .collect(),
-        ));
+    pub(crate) fn enter_stock_round(&mut self) {
+        self.round = RoundId::StockRound(StockRound::new(true));
+        self.current_player = self.priority_player;
+        self.passes = 0;
+    }
+
+    pub(crate) fn enter_operating_round(&mut self, operating_rounds_left: u32) {
         self.operate_priv_coms();
-        self.current_player = self.president(self.current_pub_com()).unwrap();
+        if self.pub_coms.is_empty() {
+            self.enter_stock_round();
+        } else {
+            self.round = RoundId::OperatingRound(OperatingRound::new(
+                operating_rounds_left,
+                self.pub_coms.keys().copied().collect(),
+            ));
+            self.start_next_pub_com();
+        }
     }
 
     pub(crate) fn operate_priv_coms(&mut self) {
         for player in &mut self.players {
-            player.operate_priv_coms();
+            let revenue = player.priv_com_revenue();
+            self.bank.withdraw(revenue);
+            player.add_capital(revenue);
+        }
+        for pub_com in self.pub_coms.values_mut() {
+            let revenue = pub_com.priv_com_revenue();
+            self.bank.withdraw(revenue);
+            pub_com.add_capital(revenue);
         }
     }
 
@@ -217,22 +388,81 @@ impl Game {
             }
         }
     }
+}
 
-    fn current_pub_com(&self) -> PubComId {
-        if let RoundId::OperatingRound(operating_round) = &self.round {
-            operating_round
-                .pub_coms_to_operate()
-                .iter()
-                .map(|&p| (p, self.stock_chart.value(p).unwrap()))
-                .max_by(|(_, v_1), (_, v_2)| u32::cmp(v_1, v_2))
-                .unwrap()
-                .0
-        } else {
-            panic!(ACTION_FORBIDDEN);
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Returns a new game for `player_count` players using the embedded configuration, with the
+    /// SRR as the public company that comes with Cornelius Vanderbilt
+    pub(crate) fn game(player_count: usize) -> Game {
+        let config = GameConfig::new().cv_pub_com(PubComId::SRR);
+        Game::with_config(player_count, &config).unwrap()
+    }
+
+    /// Returns a new game for `player_count` players in a stock round
+    pub(crate) fn stock_round_game(player_count: usize, sell_allowed: bool) -> Game {
+        let mut game = game(player_count);
+        game.round = RoundId::StockRound(StockRound::new(sell_allowed));
+        game
+    }
+
+    /// Pars `pub_com` at `par`, handing its presidency from the IPO to `player`
+    pub(crate) fn par(game: &mut Game, pub_com: PubComId, par: u32, player: usize) {
+        game.ipo.remove_presidency(pub_com);
+        game.players[player].shares_mut().add_shares(pub_com, 2);
+        game.players[player].shares_mut().add_presidency(pub_com);
+        game.par_track.add_token(pub_com, par);
+        game.stock_chart.add_token(pub_com, par);
+    }
+
+    /// Pars and floats `pub_com` at `par` with `player` as its president
+    pub(crate) fn float(game: &mut Game, pub_com: PubComId, par_value: u32, player: usize) {
+        par(game, pub_com, par_value, player);
+        game.ipo.remove_shares(pub_com, 3);
+        game.players[player].shares_mut().add_shares(pub_com, 3);
+        game.bank.withdraw(10 * par_value);
+        let mut floated = PubCom::new(pub_com, 10 * par_value);
+        floated.use_station();
+        game.pub_coms.insert(pub_com, floated);
+        game.map.place_home_station(pub_com);
+    }
+
+    #[test]
+    fn with_config_rejects_player_count() {
+        for &player_count in &[0, 1, 7] {
+            let errors = Game::with_config(player_count, &GameConfig::new()).unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].file(), "GameConfig");
+            assert_eq!(errors[0].path(), "player_count");
         }
+        assert!(Game::with_config(2, &GameConfig::new()).is_ok());
+        assert!(Game::with_config(6, &GameConfig::new()).is_ok());
     }
-}
 
-fn read_toml_file(name: &str) -> String {
-    fs::read_to_string(format!("conf/{}.toml", name)).unwrap()
+    #[test]
+    fn pass_in_stock_round() {
+        let mut game = stock_round_game(4, true);
+        assert_eq!(game.pass(), Ok(()));
+        assert_eq!(game.current_player(), 1);
+    }
+
+    #[test]
+    fn pass_after_action_is_rejected() {
+        let mut game = stock_round_game(4, true);
+        par(&mut game, PubComId::PRR, 80, 0);
+        game.players[0].shares_mut().add_shares(PubComId::PRR, 1);
+        game.players[1].shares_mut().add_shares(PubComId::PRR, 2);
+        game.sell_shares(PubComId::PRR, 1).unwrap();
+        assert_eq!(game.check_pass(), Err(ActionError::ActionAlreadyPerformed));
+    }
+
+    #[test]
+    fn pass_over_certificate_limit_is_rejected() {
+        let mut game = stock_round_game(4, true);
+        game.players[0].shares_mut().add_shares(PubComId::PRR, 9);
+        game.players[0].shares_mut().add_shares(PubComId::LV, 8);
+        assert_eq!(game.check_pass(), Err(ActionError::CertificateLimit));
+    }
 }
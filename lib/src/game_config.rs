@@ -0,0 +1,363 @@
+use crate::economy::{ParTrack, StockChart};
+use crate::geography::{Map, TileSet};
+use crate::toml_reader::index_path;
+use crate::{Capitalization, ConfigError, PresidencySource, PubComId, TrainSet};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+
+static MAP_TOML: &str = include_str!("../conf/map.toml");
+static TILE_SET_TOML: &str = include_str!("../conf/tile_set.toml");
+static TRAIN_SET_TOML: &str = include_str!("../conf/train_set.toml");
+static STOCK_CHART_TOML: &str = include_str!("../conf/stock_chart.toml");
+static PAR_TRACK_TOML: &str = include_str!("../conf/par_track.toml");
+static BANK_SIZE: u32 = 8000;
+static FLOAT_PERCENT: u32 = 60;
+
+/// Represents the configuration of a game, defaulting to the embedded 18Chesapeake configuration
+#[derive(Clone, Debug, Default)]
+pub struct GameConfig {
+    map: Source<Map>,
+    tile_set: Source<TileSet>,
+    train_set: Source<TrainSet>,
+    stock_chart: Source<StockChart>,
+    par_track: Source<ParTrack>,
+    bank_size: Option<u32>,
+    cv_pub_com: Option<PubComId>,
+    float_percent: Option<u32>,
+    capitalization: Capitalization,
+    presidency_source: PresidencySource,
+}
+
+impl GameConfig {
+    /// Returns a `GameConfig` using the embedded 18Chesapeake configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses the map TOML in `s`
+    pub fn map_str(mut self, s: impl Into<String>) -> Self {
+        self.map = Source::Str(s.into());
+        self
+    }
+
+    /// Uses the map TOML in the file at `path`
+    pub fn map_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.map = Source::Path(path.into());
+        self
+    }
+
+    /// Uses `map`
+    pub fn map(mut self, map: Map) -> Self {
+        self.map = Source::Value(map);
+        self
+    }
+
+    /// Uses the tile set TOML in `s`
+    pub fn tile_set_str(mut self, s: impl Into<String>) -> Self {
+        self.tile_set = Source::Str(s.into());
+        self
+    }
+
+    /// Uses the tile set TOML in the file at `path`
+    pub fn tile_set_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.tile_set = Source::Path(path.into());
+        self
+    }
+
+    /// Uses `tile_set`
+    pub fn tile_set(mut self, tile_set: TileSet) -> Self {
+        self.tile_set = Source::Value(tile_set);
+        self
+    }
+
+    /// Uses the train set TOML in `s`
+    pub fn train_set_str(mut self, s: impl Into<String>) -> Self {
+        self.train_set = Source::Str(s.into());
+        self
+    }
+
+    /// Uses the train set TOML in the file at `path`
+    pub fn train_set_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.train_set = Source::Path(path.into());
+        self
+    }
+
+    /// Uses `train_set`
+    pub fn train_set(mut self, train_set: TrainSet) -> Self {
+        self.train_set = Source::Value(train_set);
+        self
+    }
+
+    /// Uses the stock chart TOML in `s`
+    pub fn stock_chart_str(mut self, s: impl Into<String>) -> Self {
+        self.stock_chart = Source::Str(s.into());
+        self
+    }
+
+    /// Uses the stock chart TOML in the file at `path`
+    pub fn stock_chart_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.stock_chart = Source::Path(path.into());
+        self
+    }
+
+    /// Uses `stock_chart`
+    pub fn stock_chart(mut self, stock_chart: StockChart) -> Self {
+        self.stock_chart = Source::Value(stock_chart);
+        self
+    }
+
+    /// Uses the par track TOML in `s`
+    pub fn par_track_str(mut self, s: impl Into<String>) -> Self {
+        self.par_track = Source::Str(s.into());
+        self
+    }
+
+    /// Uses the par track TOML in the file at `path`
+    pub fn par_track_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.par_track = Source::Path(path.into());
+        self
+    }
+
+    /// Uses `par_track`
+    pub fn par_track(mut self, par_track: ParTrack) -> Self {
+        self.par_track = Source::Value(par_track);
+        self
+    }
+
+    /// Uses a bank starting with `bank_size`, out of which the players are paid their starting
+    /// capital, instead of 8000
+    pub fn bank_size(mut self, bank_size: u32) -> Self {
+        self.bank_size = Some(bank_size);
+        self
+    }
+
+    /// Uses `pub_com` for the share that comes with Cornelius Vanderbilt instead of a random
+    /// public company other than the B&O, e.g. to replay a game
+    pub fn cv_pub_com(mut self, pub_com: PubComId) -> Self {
+        self.cv_pub_com = Some(pub_com);
+        self
+    }
+
+    /// Floats public companies once `float_percent` percent of their shares have been sold
+    /// instead of 60, which must be a multiple of 10 between 20 and 100
+    pub fn float_percent(mut self, float_percent: u32) -> Self {
+        self.float_percent = Some(float_percent);
+        self
+    }
+
+    /// Pays public companies for the shares sold from the IPO using `capitalization` instead of
+    /// full capitalization
+    pub fn capitalization(mut self, capitalization: Capitalization) -> Self {
+        self.capitalization = capitalization;
+        self
+    }
+
+    /// Pays `presidency_source` for the presidencies under incremental capitalization instead of
+    /// the public companies
+    pub fn presidency_source(mut self, presidency_source: PresidencySource) -> Self {
+        self.presidency_source = presidency_source;
+        self
+    }
+
+    pub(crate) fn load(&self) -> Result<Config, Vec<ConfigError>> {
+        let map = self.map.load("map.toml", MAP_TOML, Map::from_toml);
+        let tile_set = self
+            .tile_set
+            .load("tile_set.toml", TILE_SET_TOML, TileSet::from_toml);
+        let train_set = self
+            .train_set
+            .load("train_set.toml", TRAIN_SET_TOML, TrainSet::from_toml);
+        let stock_chart =
+            self.stock_chart
+                .load("stock_chart.toml", STOCK_CHART_TOML, StockChart::from_toml);
+        let par_track = self
+            .par_track
+            .load("par_track.toml", PAR_TRACK_TOML, ParTrack::from_toml);
+        let par_track = match (&stock_chart, par_track) {
+            (Ok(stock_chart), Ok(par_track)) => check_pars(
+                &self.par_track.file("par_track.toml"),
+                stock_chart,
+                par_track,
+            ),
+            (_, par_track) => par_track,
+        };
+        let float_percent = self.float_percent.unwrap_or(FLOAT_PERCENT);
+        let float_percent =
+            if (20..=100).contains(&float_percent) && float_percent.is_multiple_of(10) {
+                Ok(float_percent)
+            } else {
+                Err(vec![ConfigError::new(
+                    "GameConfig",
+                    "float_percent",
+                    format!(
+                        "is not a multiple of 10 between 20 and 100: {}",
+                        float_percent
+                    ),
+                )])
+            };
+        match (
+            map,
+            tile_set,
+            train_set,
+            stock_chart,
+            par_track,
+            float_percent,
+        ) {
+            (
+                Ok(map),
+                Ok(tile_set),
+                Ok(train_set),
+                Ok(stock_chart),
+                Ok(par_track),
+                Ok(float_percent),
+            ) => Ok(Config {
+                map,
+                tile_set,
+                train_set,
+                stock_chart,
+                par_track,
+                bank_size: self.bank_size.unwrap_or(BANK_SIZE),
+                cv_pub_com: self.cv_pub_com.unwrap_or_else(random_cv_pub_com),
+                float_percent,
+                capitalization: self.capitalization,
+                presidency_source: self.presidency_source,
+            }),
+            (map, tile_set, train_set, stock_chart, par_track, float_percent) => Err(map
+                .err()
+                .into_iter()
+                .chain(tile_set.err())
+                .chain(train_set.err())
+                .chain(stock_chart.err())
+                .chain(par_track.err())
+                .chain(float_percent.err())
+                .flatten()
+                .collect()),
+        }
+    }
+}
+
+/// The loaded parts of a `GameConfig`
+pub(crate) struct Config {
+    pub(crate) map: Map,
+    pub(crate) tile_set: TileSet,
+    pub(crate) train_set: TrainSet,
+    pub(crate) stock_chart: StockChart,
+    pub(crate) par_track: ParTrack,
+    pub(crate) bank_size: u32,
+    pub(crate) cv_pub_com: PubComId,
+    pub(crate) float_percent: u32,
+    pub(crate) capitalization: Capitalization,
+    pub(crate) presidency_source: PresidencySource,
+}
+
+fn check_pars(
+    file: &str,
+    stock_chart: &StockChart,
+    par_track: ParTrack,
+) -> Result<ParTrack, Vec<ConfigError>> {
+    let errors: Vec<_> = par_track
+        .values()
+        .iter()
+        .enumerate()
+        .filter(|&(_, &par)| stock_chart.par_cell(par).is_none())
+        .map(|(i, par)| {
+            ConfigError::new(
+                file,
+                &index_path("values", i),
+                format!("is not on the stock chart: {}", par),
+            )
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(par_track)
+    } else {
+        Err(errors)
+    }
+}
+
+fn random_cv_pub_com() -> PubComId {
+    let pub_coms: Vec<_> = PubComId::values()
+        .filter(|&p| p != PubComId::BAndO)
+        .collect();
+    let random = RandomState::new().build_hasher().finish();
+    pub_coms[random as usize % pub_coms.len()]
+}
+
+#[derive(Clone, Debug, Default)]
+enum Source<T> {
+    #[default]
+    Default,
+    Str(String),
+    Path(PathBuf),
+    Value(T),
+}
+
+impl<T: Clone> Source<T> {
+    fn file(&self, default: &str) -> String {
+        match self {
+            Source::Path(path) => path.display().to_string(),
+            _ => default.to_string(),
+        }
+    }
+
+    fn load(
+        &self,
+        file: &str,
+        default: &str,
+        from_toml: fn(&str, &str) -> Result<T, Vec<ConfigError>>,
+    ) -> Result<T, Vec<ConfigError>> {
+        match self {
+            Source::Default => from_toml(file, default),
+            Source::Str(s) => from_toml(file, s),
+            Source::Path(path) => {
+                let file = self.file(file);
+                match fs::read_to_string(path) {
+                    Ok(s) => from_toml(&file, &s),
+                    Err(err) => Err(vec![ConfigError::new(
+                        &file,
+                        "",
+                        format!("can not be read: {}", err),
+                    )]),
+                }
+            }
+            Source::Value(value) => Ok(value.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_loads() {
+        assert!(GameConfig::new().load().is_ok());
+    }
+
+    #[test]
+    fn errors_are_collected_from_every_file() {
+        let errors = GameConfig::new()
+            .map_str("hexes = [")
+            .train_set_path("does/not/exist.toml")
+            .load()
+            .err()
+            .unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].file(), "map.toml");
+        assert_eq!(errors[1].file(), "does/not/exist.toml");
+    }
+
+    #[test]
+    fn par_values_off_the_stock_chart_are_rejected() {
+        let errors = GameConfig::new()
+            .par_track_str("values = [70, 75, 95]")
+            .load()
+            .err()
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].file(), "par_track.toml");
+        assert_eq!(errors[0].path(), "values[1]");
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+static DIRECTIONS: [(isize, isize); 6] = [(0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1), (1, 0)];
+
+/// Returns the edge of a neighboring hex that touches `edge`
+pub fn opposite_edge(edge: u32) -> u32 {
+    (edge + 3) % 6
+}
+
+/// Represents the position of a hex on a map as its column `x` and its row `y` within the column,
+/// with the hexes flat-topped and the odd columns shifted down half a hex
+///
+/// The edges of a hex are numbered clockwise from 0 at the bottom, so that edge 3 is at the top.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct HexCoord {
+    x: usize,
+    y: usize,
+}
+
+impl HexCoord {
+    /// Returns the `HexCoord` at column `x` and row `y`
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// Returns the `HexCoord` at the axial coordinates `q`, `r`, if it is not off the top or left
+    /// of the map
+    pub fn from_axial(q: isize, r: isize) -> Option<Self> {
+        let y = r + (q - q.rem_euclid(2)) / 2;
+        if q < 0 || y < 0 {
+            None
+        } else {
+            Some(Self::new(q as usize, y as usize))
+        }
+    }
+
+    /// Returns the `HexCoord` at the cube coordinates `q`, `r`, `s`, if they sum to zero and it is
+    /// not off the top or left of the map
+    pub fn from_cube(q: isize, r: isize, s: isize) -> Option<Self> {
+        if q + r + s == 0 {
+            Self::from_axial(q, r)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the `HexCoord` with the printed board label `label`, e.g. "D7", if it is a label
+    pub fn from_label(label: &str) -> Option<Self> {
+        let split = label.find(|c: char| !c.is_ascii_uppercase())?;
+        let (letters, number) = label.split_at(split);
+        if letters.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let x = letters.bytes().try_fold(0usize, |x, b| {
+            x.checked_mul(26)?.checked_add((b - b'A') as usize + 1)
+        })? - 1;
+        let number: usize = number.parse().ok()?;
+        if number == 0 || number % 2 == x % 2 {
+            None
+        } else {
+            Some(Self::new(x, (number - 1) / 2))
+        }
+    }
+
+    /// Returns the column of this `HexCoord`
+    pub fn x(self) -> usize {
+        self.x
+    }
+
+    /// Returns the row of this `HexCoord` within its column
+    pub fn y(self) -> usize {
+        self.y
+    }
+
+    /// Returns the axial coordinates `q`, `r` of this `HexCoord`
+    pub fn to_axial(self) -> (isize, isize) {
+        let (x, y) = (self.x as isize, self.y as isize);
+        (x, y - (x - x % 2) / 2)
+    }
+
+    /// Returns the cube coordinates `q`, `r`, `s` of this `HexCoord`
+    pub fn to_cube(self) -> (isize, isize, isize) {
+        let (q, r) = self.to_axial();
+        (q, r, -q - r)
+    }
+
+    /// Returns the `HexCoord` across `edge` of this `HexCoord`, if `edge` is an edge and it is not
+    /// off the top or left of the map
+    pub fn neighbor(self, edge: u32) -> Option<Self> {
+        let &(dq, dr) = DIRECTIONS.get(edge as usize)?;
+        let (q, r) = self.to_axial();
+        Self::from_axial(q + dq, r + dr)
+    }
+
+    /// Returns the number of steps between this `HexCoord` and `other`
+    pub fn distance(self, other: Self) -> usize {
+        let (q, r, s) = self.to_cube();
+        let (oq, or, os) = other.to_cube();
+        (q - oq)
+            .unsigned_abs()
+            .max((r - or).unsigned_abs())
+            .max((s - os).unsigned_abs())
+    }
+
+    /// Returns the column letters of the printed board label of this `HexCoord`, e.g. "D"
+    pub fn letters(self) -> String {
+        let mut letters = Vec::new();
+        let mut x = self.x + 1;
+        while x > 0 {
+            letters.push(b'A' + ((x - 1) % 26) as u8);
+            x = (x - 1) / 26;
+        }
+        letters.iter().rev().map(|&b| b as char).collect()
+    }
+
+    /// Returns the row number of the printed board label of this `HexCoord`, which is odd in
+    /// even columns and even in odd columns, e.g. 7
+    pub fn number(self) -> usize {
+        2 * self.y + 1 + self.x % 2
+    }
+}
+
+impl Display for HexCoord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}{}", self.letters(), self.number())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axial_round_trip() {
+        for x in 0..12 {
+            for y in 0..7 {
+                let coord = HexCoord::new(x, y);
+                let (q, r) = coord.to_axial();
+                assert_eq!(HexCoord::from_axial(q, r), Some(coord));
+                let (q, r, s) = coord.to_cube();
+                assert_eq!(q + r + s, 0);
+                assert_eq!(HexCoord::from_cube(q, r, s), Some(coord));
+            }
+        }
+        assert_eq!(HexCoord::from_axial(-1, 0), None);
+        assert_eq!(HexCoord::from_cube(0, 0, 1), None);
+    }
+
+    #[test]
+    fn neighbor_across_opposite_edge() {
+        for x in 0..12 {
+            for y in 0..7 {
+                let coord = HexCoord::new(x, y);
+                for edge in 0..6 {
+                    if let Some(neighbor) = coord.neighbor(edge) {
+                        assert_eq!(neighbor.neighbor(opposite_edge(edge)), Some(coord));
+                        assert_eq!(coord.distance(neighbor), 1);
+                    }
+                }
+                assert_eq!(coord.neighbor(6), None);
+            }
+        }
+        assert_eq!(HexCoord::new(0, 0).neighbor(3), None);
+        assert_eq!(HexCoord::new(0, 0).neighbor(1), None);
+        assert_eq!(HexCoord::new(1, 0).neighbor(2), Some(HexCoord::new(0, 0)));
+        assert_eq!(HexCoord::new(1, 0).neighbor(1), Some(HexCoord::new(0, 1)));
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(HexCoord::new(0, 0).distance(HexCoord::new(0, 0)), 0);
+        assert_eq!(HexCoord::new(0, 0).distance(HexCoord::new(0, 3)), 3);
+        assert_eq!(HexCoord::new(0, 0).distance(HexCoord::new(3, 3)), 5);
+        assert_eq!(HexCoord::new(0, 3).distance(HexCoord::new(4, 1)), 4);
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(HexCoord::new(0, 1).to_string(), "A3");
+        assert_eq!(HexCoord::new(1, 0).to_string(), "B2");
+        assert_eq!(HexCoord::new(26, 0).to_string(), "AA1");
+        assert_eq!(HexCoord::from_label("A3"), Some(HexCoord::new(0, 1)));
+        assert_eq!(HexCoord::from_label("B2"), Some(HexCoord::new(1, 0)));
+        assert_eq!(HexCoord::from_label("AA1"), Some(HexCoord::new(26, 0)));
+        for x in 0..30 {
+            for y in 0..7 {
+                let coord = HexCoord::new(x, y);
+                assert_eq!(HexCoord::from_label(&coord.to_string()), Some(coord));
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_labels() {
+        for label in ["", "A", "3", "A2", "B3", "A0", "a3", "A+3", "A3B"].iter() {
+            assert_eq!(HexCoord::from_label(label), None, "{}", label);
+        }
+        assert_eq!(HexCoord::from_label(&format!("{}1", "Z".repeat(40))), None);
+    }
+}
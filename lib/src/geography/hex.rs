@@ -1,74 +1,296 @@
-use super::{City, Tile};
-use crate::{PrivComId, PubComId, TerrainId};
+use super::{City, Rail, Stop, Tile};
+use crate::toml_reader::{index_path, key_path, TomlReader};
+use crate::{ActionError, ColorId, PrivComId, PubComId, TerrainId};
+use serde::{Deserialize, Serialize};
 use toml::Value;
 
-static TERRAIN_TYPEERROR: &str = "terrain is not of type String";
-static PRIVATE_TYPEERROR: &str = "private is not of type String";
-static CITIES_TYPEERROR: &str = "cities is not of type Array";
-
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// Represents a hex
 pub struct Hex {
     terrain: TerrainId,
     content: Option<Content>,
     private: Option<PrivComId>,
+    upgrades: Vec<i32>,
+    tile_id: Option<i32>,
 }
 
 impl Hex {
-    pub(crate) fn from_toml(toml: &Value) -> Self {
-        let terrain = toml
-            .get("terrain")
-            .map(|t| t.as_str().expect(TERRAIN_TYPEERROR))
-            .map(|t| t.parse::<TerrainId>().unwrap())
-            .unwrap_or_default();
-        let private = toml
-            .get("private")
-            .map(|p| p.as_str().expect(PRIVATE_TYPEERROR))
-            .map(|p| p.parse::<PrivComId>().unwrap());
+    pub(crate) fn from_toml(toml: &Value, path: &str, reader: &mut TomlReader) -> Option<Self> {
+        let terrain = match toml.get("terrain") {
+            Some(_) => reader.opt_parsed::<TerrainId>(toml, path, "terrain"),
+            None => Some(TerrainId::default()),
+        };
+        let private = match toml.get("private") {
+            Some(_) => reader
+                .opt_parsed::<PrivComId>(toml, path, "private")
+                .map(Some),
+            None => Some(None),
+        };
         let tile = toml
             .get("rails")
-            .and(toml.get("color"))
-            .map(|_| Content::Tile(Tile::from_toml(&toml)));
-        let cities = toml.get("cities").map(|cs| {
-            Content::Cities(
-                cs.as_array()
-                    .expect(CITIES_TYPEERROR)
-                    .iter()
-                    .map(|c| City::from_toml(c))
-                    .collect(),
-            )
+            .or_else(|| toml.get("color"))
+            .map(|_| Tile::from_toml(toml, path, reader).map(Content::Tile));
+        let cities_path = key_path(path, "cities");
+        let cities = reader.opt_array(toml, path, "cities").map(|cities_toml| {
+            let mut cities = Vec::new();
+            for (i, value) in cities_toml.iter().enumerate() {
+                cities.push(City::from_toml(value, &index_path(&cities_path, i), reader));
+            }
+            cities
+                .into_iter()
+                .collect::<Option<Vec<_>>>()
+                .map(Content::Cities)
         });
-        Self {
-            terrain,
-            content: tile.or(cities),
-            private,
-        }
+        let content = match (tile, cities) {
+            (Some(_), Some(_)) => {
+                reader.error(path, "has both rails and cities".to_string());
+                return None;
+            }
+            (Some(tile), None) => Some(tile?),
+            (None, Some(cities)) => Some(cities?),
+            (None, None) if toml.get("cities").is_some() => return None,
+            (None, None) => None,
+        };
+        let upgrades = match content {
+            Some(Content::Tile(_)) => Some(Vec::new()),
+            _ => Tile::read_upgrades(toml, path, reader),
+        };
+        Some(Self {
+            terrain: terrain?,
+            content,
+            private: private?,
+            upgrades: upgrades?,
+            tile_id: None,
+        })
+    }
+
+    /// Returns the index of the city a home station of `pub_com` would be placed in, preferring
+    /// the city at `edge` if there are several, or `None` if no city has a free spot
+    pub(crate) fn home_city_index(&self, pub_com: PubComId, edge: Option<u32>) -> Option<usize> {
+        let stops: Vec<(&[u32], bool)> = match &self.content {
+            Some(Content::Tile(tile)) => tile
+                .rails()
+                .iter()
+                .filter_map(|r| r.stop().map(|s| (r.edges(), s.station_allowed(pub_com))))
+                .collect(),
+            Some(Content::Cities(cities)) => cities
+                .iter()
+                .map(|c| (&[][..], c.station_allowed(pub_com)))
+                .collect(),
+            None => Vec::new(),
+        };
+        edge.and_then(|edge| {
+            stops
+                .iter()
+                .position(|&(edges, allowed)| allowed && edges.contains(&edge))
+        })
+        .or_else(|| stops.iter().position(|&(_, allowed)| allowed))
     }
 
-    pub(crate) fn place_station(&mut self, pub_com: PubComId, edge: Option<u32>) {
-        if let Some(content) = &mut self.content {
-            match content {
-                Content::Tile(tile) => {
-                    if let Some(edge) = edge {
-                        for rail in tile.rails_mut() {
-                            if rail.edges().contains(&edge) {
-                                rail.place_station(pub_com);
-                                break;
-                            }
-                        }
-                    } else {
-                        tile.rails_mut()[0].place_station(pub_com);
-                    }
+    pub(crate) fn place_station_in_city(&mut self, pub_com: PubComId, index: usize) {
+        match &mut self.content {
+            Some(Content::Tile(tile)) => {
+                if let Some(rail) = tile
+                    .rails_mut()
+                    .iter_mut()
+                    .filter(|r| r.stop().is_some())
+                    .nth(index)
+                {
+                    rail.place_station(pub_com);
                 }
-                Content::Cities(cities) => {
-                    cities[0].place_station(pub_com);
+            }
+            Some(Content::Cities(cities)) => cities[index].place_station(pub_com),
+            None => unreachable!(),
+        }
+    }
+
+    pub(crate) fn check_lay_tile(&self, id: i32, tile: &Tile) -> Result<(), ActionError> {
+        let allowed = match &self.content {
+            Some(Content::Tile(old_tile)) => {
+                old_tile.upgrades().contains(&id)
+                    && old_tile.color().upgraded() == Some(tile.color())
+                    && old_tile.rail_mapping(tile).is_some()
+            }
+            Some(Content::Cities(cities)) => self.yellow_allowed(id, tile, cities),
+            None => self.yellow_allowed(id, tile, &[]),
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(ActionError::InvalidUpgrade)
+        }
+    }
+
+    /// Lays `tile` on this `Hex`, carrying over cities and their stations, and returns for each
+    /// city of this `Hex` its stop index in `tile`
+    pub(crate) fn lay_tile(&mut self, id: i32, mut tile: Tile) -> Vec<usize> {
+        let cities: Vec<City> = self.cities().into_iter().cloned().collect();
+        let rail_indices = self.city_rails(&tile);
+        for (city, &i) in cities.iter().zip(&rail_indices) {
+            tile.rails_mut()[i].carry_over(city);
+        }
+        let city_indices = rail_indices
+            .iter()
+            .map(|&i| {
+                tile.rails()[..i]
+                    .iter()
+                    .filter(|r| r.stop().is_some())
+                    .count()
+            })
+            .collect();
+        self.content = Some(Content::Tile(tile));
+        self.tile_id = Some(id);
+        city_indices
+    }
+
+    pub(crate) fn city_edges(&self, index: usize) -> &[u32] {
+        self.rails()
+            .iter()
+            .filter(|r| r.stop().is_some())
+            .nth(index)
+            .map_or(&[], |r| r.edges())
+    }
+
+    /// Returns for each city of this `Hex` the index of the rail of `tile` it is carried over to
+    /// when `tile` is laid on this `Hex`, which must be allowed
+    pub(crate) fn city_rails(&self, tile: &Tile) -> Vec<usize> {
+        match &self.content {
+            Some(Content::Tile(old_tile)) => old_tile
+                .rails()
+                .iter()
+                .zip(old_tile.rail_mapping(tile).unwrap())
+                .filter(|(r, _)| matches!(r.stop(), Some(Stop::City(_))))
+                .map(|(_, i)| i)
+                .collect(),
+            Some(Content::Cities(cities)) => Self::city_mapping(cities, tile).unwrap(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the index of the rail of stop `index` of this `Hex`, if any
+    pub(crate) fn stop_rail(&self, index: usize) -> Option<usize> {
+        let rails = self.rails();
+        (0..rails.len())
+            .filter(|&i| rails[i].stop().is_some())
+            .nth(index)
+    }
+
+    /// Returns the stop index of rail `index` of this `Hex`
+    pub(crate) fn rail_stop(&self, index: usize) -> usize {
+        self.rails()[..index]
+            .iter()
+            .filter(|r| r.stop().is_some())
+            .count()
+    }
+
+    fn yellow_allowed(&self, id: i32, tile: &Tile, cities: &[City]) -> bool {
+        let listed = if self.upgrades.is_empty() {
+            id >= 0
+        } else {
+            self.upgrades.contains(&id)
+        };
+        listed && tile.color() == ColorId::Yellow && Self::city_mapping(cities, tile).is_some()
+    }
+
+    /// Returns for each city in `cities` the index of the rail of `tile` it is carried over to,
+    /// or `None` if the stops of `tile` do not match `cities`
+    fn city_mapping(cities: &[City], tile: &Tile) -> Option<Vec<usize>> {
+        if tile
+            .rails()
+            .iter()
+            .any(|r| matches!(r.stop(), Some(Stop::Location(_))))
+        {
+            return None;
+        }
+        let mut unused: Vec<usize> = (0..tile.rails().len())
+            .filter(|&i| tile.rails()[i].stop().is_some())
+            .collect();
+        if unused.len() != cities.len() {
+            return None;
+        }
+        let mut mapping = Vec::with_capacity(cities.len());
+        for city in cities {
+            let position = unused.iter().position(|&i| match tile.rails()[i].stop() {
+                Some(Stop::City(upgrade)) => (city.spots() == 0) == (upgrade.spots() == 0),
+                _ => false,
+            })?;
+            mapping.push(unused.remove(position));
+        }
+        Some(mapping)
+    }
+
+    /// Returns the tile laid on this `Hex`, if any
+    pub fn tile(&self) -> Option<&Tile> {
+        if let Some(Content::Tile(tile)) = &self.content {
+            Some(tile)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the rails of this `Hex`
+    pub fn rails(&self) -> &[Rail] {
+        self.tile().map_or(&[], |t| t.rails())
+    }
+
+    /// Returns the id of the tile laid on this `Hex`, or `None` if no tile has been laid from the
+    /// tile set
+    pub fn tile_id(&self) -> Option<i32> {
+        self.tile_id
+    }
+
+    /// Returns the number of stops of this `Hex`, cities and off-board locations alike, which
+    /// stations are indexed by
+    pub fn stop_count(&self) -> usize {
+        match &self.content {
+            Some(Content::Tile(tile)) => tile.rails().iter().filter(|r| r.stop().is_some()).count(),
+            Some(Content::Cities(cities)) => cities.len(),
+            None => 0,
+        }
+    }
+
+    /// Returns the cities of this `Hex`
+    pub fn cities(&self) -> Vec<&City> {
+        match &self.content {
+            Some(Content::Tile(tile)) => tile
+                .rails()
+                .iter()
+                .filter_map(|r| match r.stop() {
+                    Some(Stop::City(city)) => Some(city),
+                    _ => None,
+                })
+                .collect(),
+            Some(Content::Cities(cities)) => cities.iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns stop `index` of this `Hex` if it is a city, counting off-board locations as stops
+    pub(crate) fn city(&self, index: usize) -> Option<&City> {
+        match &self.content {
+            Some(Content::Tile(tile)) => {
+                match tile.rails().iter().filter_map(|r| r.stop()).nth(index) {
+                    Some(Stop::City(city)) => Some(city),
+                    _ => None,
                 }
             }
+            Some(Content::Cities(cities)) => cities.get(index),
+            None => None,
         }
     }
+
+    /// Returns the terrain of this `Hex`
+    pub fn terrain(&self) -> TerrainId {
+        self.terrain
+    }
+
+    /// Returns the private company reserving this `Hex`, if any
+    pub fn private(&self) -> Option<PrivComId> {
+        self.private
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 enum Content {
     Tile(Tile),
     Cities(Vec<City>),
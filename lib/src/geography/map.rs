@@ -1,123 +1,463 @@
-use super::Hex;
-use crate::geography::TrackLayMap;
-use crate::{PubComId, INVALID_TOML};
-use std::collections::HashMap;
+use super::{opposite_edge, Hex, HexCoord, Stop, Tile, TileSet, TrackLayMap};
+use crate::toml_reader::{index_path, key_path, TomlReader};
+use crate::{ActionError, ColorId, ConfigError, PhaseId, PubComId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use toml::Value;
 
-static WIDTH_MISSING: &str = "width is missing";
-static WIDTH_TYPEERROR: &str = "width is not of type Integer";
-static HEIGHT_MISSING: &str = "height is missing";
-static HEIGHT_TYPEERROR: &str = "height is not of type Integer";
-static HEXES_MISSING: &str = "hexes is missing";
-static HEXES_TYPEERROR: &str = "hexes is not of type Array";
-static X_MISSING: &str = "x is missing";
-static X_TYPEERROR: &str = "x is not of type Integer";
-static Y_MISSING: &str = "y is missing";
-static Y_TYPEERROR: &str = "y is not of type Integer";
-static HOMES_MISSING: &str = "homes is missing";
-static HOMES_TYPEERROR: &str = "homes is not of type Array";
-static ID_MISSING: &str = "id is missing";
-static ID_TYPEERROR: &str = "id is not of type String";
-static EDGE_TYPEERROR: &str = "edge is not of type Integer";
-
 /// Represents the map that a game is played on
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Map {
     width: usize,
     height: usize,
     hexes: Vec<Vec<Option<Hex>>>,
+    #[serde(serialize_with = "crate::sorted_serde::map")]
     homes: HashMap<PubComId, (usize, usize, Option<u32>)>,
-    stations: HashMap<PubComId, Vec<(usize, usize, Option<u32>)>>,
+    #[serde(serialize_with = "crate::sorted_serde::map")]
+    stations: HashMap<PubComId, Vec<(usize, usize, usize)>>,
 }
 
 impl Map {
-    pub(crate) fn from_toml(s: &str) -> Self {
-        let toml = s.parse::<Value>().expect(INVALID_TOML);
-        let width = toml
-            .get("width")
-            .expect(WIDTH_MISSING)
-            .as_integer()
-            .expect(WIDTH_TYPEERROR) as usize;
-        let height = toml
-            .get("height")
-            .expect(HEIGHT_MISSING)
-            .as_integer()
-            .expect(HEIGHT_TYPEERROR) as usize;
-        let mut hexes = vec![vec![None; height]; width];
-        let hexes_toml = toml.get("hexes").expect(HEXES_MISSING);
-        for value in hexes_toml.as_array().expect(HEXES_TYPEERROR) {
-            let x = value
-                .get("x")
-                .expect(X_MISSING)
-                .as_integer()
-                .expect(X_TYPEERROR) as usize;
-            let y = value
-                .get("y")
-                .expect(Y_MISSING)
-                .as_integer()
-                .expect(Y_TYPEERROR) as usize;
-            if hexes[x][y].is_some() {
-                panic!("hex at x={}, y={} is not empty", x, y);
+    /// Returns the `Map` described by the TOML in `s`, naming `file` in any `ConfigError`
+    pub fn from_toml(file: &str, s: &str) -> Result<Self, Vec<ConfigError>> {
+        let mut reader = TomlReader::new(file);
+        let map = reader
+            .parse(s)
+            .and_then(|toml| Self::read(&toml, &mut reader));
+        reader.finish(map)
+    }
+
+    fn read(toml: &Value, reader: &mut TomlReader) -> Option<Self> {
+        let width = reader.unsigned(toml, "", "width").map(|w| w as usize);
+        let height = reader.unsigned(toml, "", "height").map(|h| h as usize);
+        let mut hexes = vec![vec![None; height.unwrap_or(0)]; width.unwrap_or(0)];
+        let mut hexes_valid = true;
+        if let Some(hexes_toml) = reader.array(toml, "", "hexes") {
+            for (i, value) in hexes_toml.iter().enumerate() {
+                let path = index_path("hexes", i);
+                let position = Self::read_position(value, &path, width, height, reader);
+                let hex = Hex::from_toml(value, &path, reader);
+                match (position, hex) {
+                    (Some((x, y)), Some(hex)) => {
+                        if hexes[x][y].is_some() {
+                            reader
+                                .error(&path, format!("is at x={}, y={} which is not empty", x, y));
+                            hexes_valid = false;
+                        }
+                        hexes[x][y] = Some(hex);
+                    }
+                    _ => hexes_valid = false,
+                }
             }
-            let hex = Hex::from_toml(value);
-            hexes[x][y] = Some(hex);
         }
         let mut homes = HashMap::new();
-        let homes_toml = toml.get("homes").expect(HOMES_MISSING);
-        for value in homes_toml.as_array().expect(HOMES_TYPEERROR) {
-            let id = value
-                .get("id")
-                .expect(ID_MISSING)
-                .as_str()
-                .expect(ID_TYPEERROR)
-                .parse::<PubComId>()
-                .unwrap();
-            let x = value
-                .get("x")
-                .expect(X_MISSING)
-                .as_integer()
-                .expect(X_TYPEERROR) as usize;
-            let y = value
-                .get("y")
-                .expect(Y_MISSING)
-                .as_integer()
-                .expect(Y_TYPEERROR) as usize;
-            let edge = value
-                .get("edge")
-                .map(|e| e.as_integer().expect(EDGE_TYPEERROR))
-                .map(|e| e as u32);
-            homes.insert(id, (x, y, edge));
+        let mut homes_valid = true;
+        if let Some(homes_toml) = reader.array(toml, "", "homes") {
+            for (i, value) in homes_toml.iter().enumerate() {
+                let path = index_path("homes", i);
+                let id = reader.parsed::<PubComId>(value, &path, "id");
+                let position = Self::read_position(value, &path, width, height, reader);
+                let edge = match reader.opt_unsigned(value, &path, "edge") {
+                    Some(edge) if edge > 5 => {
+                        reader.error(
+                            &key_path(&path, "edge"),
+                            format!("is not an edge: {}", edge),
+                        );
+                        None
+                    }
+                    edge => edge,
+                };
+                if let (Some(id), Some((x, y))) = (id, position) {
+                    if hexes_valid && hexes[x][y].is_none() {
+                        reader.error(&path, format!("is at x={}, y={} which is empty", x, y));
+                        homes_valid = false;
+                    } else if homes.insert(id, (x, y, edge)).is_some() {
+                        reader.error(&path, format!("is a duplicate home of {}", id));
+                        homes_valid = false;
+                    }
+                } else {
+                    homes_valid = false;
+                }
+            }
         }
         let mut stations = HashMap::new();
         for id in PubComId::values() {
             stations.insert(id, Vec::new());
         }
-        Self {
-            width,
-            height,
+        if !hexes_valid || !homes_valid {
+            return None;
+        }
+        Some(Self {
+            width: width?,
+            height: height?,
             hexes,
             homes,
             stations,
+        })
+    }
+
+    fn read_position(
+        toml: &Value,
+        path: &str,
+        width: Option<usize>,
+        height: Option<usize>,
+        reader: &mut TomlReader,
+    ) -> Option<(usize, usize)> {
+        let x = reader.unsigned(toml, path, "x").map(|x| x as usize);
+        let y = reader.unsigned(toml, path, "y").map(|y| y as usize);
+        let (x, y) = (x?, y?);
+        if x >= width? || y >= height? {
+            reader.error(path, format!("is at x={}, y={} which is off the map", x, y));
+            None
+        } else {
+            Some((x, y))
         }
     }
 
-    pub(crate) fn place_home_station(&mut self, pub_com: PubComId) {
-        if let Some(&(x, y, edge)) = self.homes.get(&pub_com) {
-            if let Some(hex) = &mut self.hexes[x][y] {
-                hex.place_station(pub_com, edge);
-            }
+    /// Returns the position of the home hex of `pub_com` and the index of the city its home
+    /// station is placed in, or `None` if it has no home or every city there is full
+    pub fn home_city(&self, pub_com: PubComId) -> Option<(usize, usize, usize)> {
+        let &(x, y, edge) = self.homes.get(&pub_com)?;
+        let city = self.hexes[x][y].as_ref()?.home_city_index(pub_com, edge)?;
+        Some((x, y, city))
+    }
+
+    /// Places the home station of `pub_com` and returns whether there was room for it
+    pub(crate) fn place_home_station(&mut self, pub_com: PubComId) -> bool {
+        if let Some((x, y, city)) = self.home_city(pub_com) {
+            self.place_station(pub_com, x, y, city);
+            true
+        } else {
+            false
         }
-        unreachable!()
     }
 
-    /// Returns the `TrackLayMap` for `pub_com` on this `Map`.
-    pub fn track_lay_map(&self, pub_com: PubComId) -> TrackLayMap {
-        let map = TrackLayMap::new();
-        if let Some(stations) = self.stations.get(&pub_com) {
-            for &(x, y, edge) in stations {}
+    pub(crate) fn place_station(&mut self, pub_com: PubComId, x: usize, y: usize, city: usize) {
+        if let Some(hex) = &mut self.hexes[x][y] {
+            hex.place_station_in_city(pub_com, city);
+            self.stations.get_mut(&pub_com).unwrap().push((x, y, city));
+        } else {
+            unreachable!();
+        }
+    }
+
+    pub(crate) fn lay_tile(&mut self, x: usize, y: usize, id: i32, tile: Tile) {
+        if let Some(hex) = &mut self.hexes[x][y] {
+            let city_indices = hex.lay_tile(id, tile);
+            for stations in self.stations.values_mut() {
+                for station in stations.iter_mut() {
+                    if station.0 == x && station.1 == y {
+                        station.2 = city_indices[station.2];
+                    }
+                }
+            }
         } else {
             unreachable!();
         }
+    }
+
+    /// Returns the width of this `Map`
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of this `Map`
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the hex at `x`, `y` on this `Map`, if any
+    pub fn hex(&self, x: usize, y: usize) -> Option<&Hex> {
+        self.hexes
+            .get(x)
+            .and_then(|c| c.get(y))
+            .and_then(|h| h.as_ref())
+    }
+
+    /// Returns the home station of `pub_com` on this `Map` as `x`, `y` and edge, if any
+    pub fn home(&self, pub_com: PubComId) -> Option<(usize, usize, Option<u32>)> {
+        self.homes.get(&pub_com).copied()
+    }
+
+    /// Returns the stations of `pub_com` on this `Map` as `x`, `y` and city index
+    pub fn stations(&self, pub_com: PubComId) -> &[(usize, usize, usize)] {
+        &self.stations[&pub_com]
+    }
+
+    /// Returns the position of the hex across `edge` of the hex at `x`, `y` on this `Map`, if any
+    pub fn neighbor(&self, x: usize, y: usize, edge: u32) -> Option<(usize, usize)> {
+        let neighbor = HexCoord::new(x, y).neighbor(edge)?;
+        let (nx, ny) = (neighbor.x(), neighbor.y());
+        self.hex(nx, ny).map(|_| (nx, ny))
+    }
+
+    /// Returns the positions of the hexes on this `Map`, column by column
+    pub fn coords(&self) -> impl Iterator<Item = HexCoord> + '_ {
+        (0..self.width)
+            .flat_map(move |x| (0..self.height).map(move |y| HexCoord::new(x, y)))
+            .filter(move |c| self.hex(c.x(), c.y()).is_some())
+    }
+
+    /// Returns the `TrackLayMap` for `pub_com` on this `Map` given the tiles in `tile_set` and
+    /// the colors allowed in `phase`
+    pub fn track_lay_map(
+        &self,
+        pub_com: PubComId,
+        tile_set: &TileSet,
+        phase: PhaseId,
+    ) -> TrackLayMap {
+        let mut map = TrackLayMap::new();
+        let reach = self.reachable_edges(pub_com);
+        let mut positions: Vec<_> = reach.keys().copied().collect();
+        positions.sort_unstable();
+        for (x, y) in positions {
+            let hex = self.hexes[x][y].as_ref().unwrap();
+            for id in tile_set.ids() {
+                let (tile, count) = tile_set.tile(id).unwrap();
+                if count == 0 || !phase.allows_color(tile.color()) {
+                    continue;
+                }
+                for rotation in 0..6 {
+                    let tile = tile.rotated(rotation);
+                    if hex.check_lay_tile(id, &tile).is_ok()
+                        && self.check_track(&reach, pub_com, x, y, &tile).is_ok()
+                    {
+                        map.insert((x, y), (id, rotation));
+                    }
+                }
+            }
+        }
+        map
+    }
+
+    /// Returns the hexes reachable by `pub_com` on this `Map` with the edges through which they
+    /// are reached
+    pub(crate) fn reachable_edges(
+        &self,
+        pub_com: PubComId,
+    ) -> HashMap<(usize, usize), HashSet<u32>> {
+        let mut reach: HashMap<(usize, usize), HashSet<u32>> = HashMap::new();
+        let mut exits = Vec::new();
+        for &(x, y, city) in &self.stations[&pub_com] {
+            reach.entry((x, y)).or_default();
+            let hex = self.hexes[x][y].as_ref().unwrap();
+            exits.extend(hex.city_edges(city).iter().map(|&e| (x, y, e)));
+        }
+        while let Some((x, y, edge)) = exits.pop() {
+            if let Some((nx, ny)) = self.neighbor(x, y, edge) {
+                let entry = opposite_edge(edge);
+                if !reach.entry((nx, ny)).or_default().insert(entry) {
+                    continue;
+                }
+                let hex = self.hexes[nx][ny].as_ref().unwrap();
+                for rail in hex.rails().iter().filter(|r| r.edges().contains(&entry)) {
+                    let passable = match rail.stop() {
+                        Some(Stop::City(city)) => !city.blocks(pub_com),
+                        Some(Stop::Location(_)) => false,
+                        None => true,
+                    };
+                    if passable {
+                        for &e in rail.edges().iter().filter(|&&e| e != entry) {
+                            exits.push((nx, ny, e));
+                        }
+                    }
+                }
+            }
+        }
+        reach
+    }
+
+    /// Returns `Ok` if `tile` laid at `x`, `y` connects to the track of `pub_com` given its
+    /// `reach`, and no track runs off this `Map` or into a blank edge of a red or gray hex. A tile
+    /// laid where `pub_com` has a station connects if it adds an edge to the rail of a stationed
+    /// city. `tile` must be a valid lay on the hex.
+    pub(crate) fn check_track(
+        &self,
+        reach: &HashMap<(usize, usize), HashSet<u32>>,
+        pub_com: PubComId,
+        x: usize,
+        y: usize,
+        tile: &Tile,
+    ) -> Result<(), ActionError> {
+        let edges = reach.get(&(x, y)).ok_or(ActionError::NotConnected)?;
+        let hex = self.hexes[x][y].as_ref().unwrap();
+        let old_edges: HashSet<u32> = hex
+            .rails()
+            .iter()
+            .flat_map(|r| r.edges())
+            .copied()
+            .collect();
+        let city_rails = hex.city_rails(tile);
+        let station_extended = self.stations[&pub_com]
+            .iter()
+            .filter(|&&(sx, sy, _)| sx == x && sy == y)
+            .filter_map(|&(_, _, city)| city_rails.get(city))
+            .any(|&i| {
+                tile.rails()[i]
+                    .edges()
+                    .iter()
+                    .any(|e| !old_edges.contains(e))
+            });
+        if !station_extended
+            && !tile
+                .rails()
+                .iter()
+                .any(|r| r.edges().iter().any(|e| edges.contains(e)))
+        {
+            return Err(ActionError::NotConnected);
+        }
+        for &edge in tile.rails().iter().flat_map(|r| r.edges()) {
+            let (nx, ny) = self.neighbor(x, y, edge).ok_or(ActionError::TrackBlocked)?;
+            if let Some(neighbor_tile) = self.hexes[nx][ny].as_ref().unwrap().tile() {
+                let entry = opposite_edge(edge);
+                if (neighbor_tile.color() == ColorId::Red || neighbor_tile.color() == ColorId::Gray)
+                    && !neighbor_tile
+                        .rails()
+                        .iter()
+                        .any(|r| r.edges().contains(&entry))
+                {
+                    return Err(ActionError::TrackBlocked);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static HEXES: &str = r#"
+        width = 3
+        height = 3
+
+        [[hexes]]
+        x = 1
+        y = 0
+
+        [[hexes]]
+        x = 0
+        y = 2
+
+        [[hexes]]
+        x = 2
+        y = 1
+
+        [[hexes]]
+        x = 2
+        y = 2
+    "#;
+
+    fn map(hexes: &str) -> Map {
+        let mut map = Map::from_toml("map.toml", &format!("{}{}", HEXES, hexes)).unwrap();
+        map.place_home_station(PubComId::PRR);
         map
     }
+
+    fn track_lays(map: &Map, tile_set: &str, phase: PhaseId) -> Vec<((usize, usize), (i32, u32))> {
+        let tile_set = TileSet::from_toml("tile_set.toml", tile_set).unwrap();
+        let track_lay_map = map.track_lay_map(PubComId::PRR, &tile_set, phase);
+        let mut track_lays: Vec<_> = track_lay_map
+            .positions()
+            .flat_map(|(x, y)| {
+                track_lay_map
+                    .track_lays(x, y)
+                    .iter()
+                    .map(move |&track_lay| ((x, y), track_lay))
+            })
+            .collect();
+        track_lays.sort_unstable();
+        track_lays
+    }
+
+    #[test]
+    fn track_lay_map_blocked_by_red_and_gray_edges() {
+        let map = map(r#"
+            [[hexes]]
+            x = 1
+            y = 1
+            cities = [{value = 20, spots = 1}]
+
+            [[hexes]]
+            x = 0
+            y = 1
+            rails = [{edges = [1], location = {values = [20, 30, 40, 50], name = "West"}}]
+            color = "Red"
+
+            [[hexes]]
+            x = 1
+            y = 2
+            rails = [{edges = [1, 4]}]
+            color = "Gray"
+
+            [[homes]]
+            id = "PRR"
+            x = 1
+            y = 1
+        "#);
+        let tile_set = r#"
+            [[tiles]]
+            id = 57
+            count = 1
+            rails = [{edges = [0, 3], city = {value = 20, spots = 1}}]
+            color = "Yellow"
+        "#;
+        assert_eq!(
+            track_lays(&map, tile_set, PhaseId::Phase2),
+            vec![((1, 1), (57, 1)), ((1, 1), (57, 4))]
+        );
+    }
+
+    #[test]
+    fn track_lay_map_requires_new_track_from_station() {
+        let map = map(r#"
+            [[hexes]]
+            x = 1
+            y = 1
+            rails = [
+                {edges = [1], city = {value = 30, spots = 1}},
+                {edges = [4], city = {value = 30, spots = 1}}
+            ]
+            color = "Yellow"
+            upgrades = [100, 101]
+
+            [[hexes]]
+            x = 0
+            y = 1
+
+            [[hexes]]
+            x = 1
+            y = 2
+
+            [[homes]]
+            id = "PRR"
+            x = 1
+            y = 1
+            edge = 1
+        "#);
+        let tile_set = r#"
+            [[tiles]]
+            id = 100
+            count = 1
+            rails = [
+                {edges = [1], city = {value = 40, spots = 1}},
+                {edges = [4, 5], city = {value = 40, spots = 1}}
+            ]
+            color = "Green"
+
+            [[tiles]]
+            id = 101
+            count = 1
+            rails = [
+                {edges = [1, 2], city = {value = 40, spots = 1}},
+                {edges = [4], city = {value = 40, spots = 1}}
+            ]
+            color = "Green"
+        "#;
+        assert_eq!(
+            track_lays(&map, tile_set, PhaseId::Phase3),
+            vec![((1, 1), (100, 3)), ((1, 1), (101, 0))]
+        );
+    }
 }
@@ -1,16 +1,20 @@
 //! Game geography logic
 
+mod coord;
 mod hex;
 mod map;
 mod rail;
+mod route;
 mod stops;
 mod tile;
 mod tile_set;
 mod track_lay_map;
 
+pub use coord::{opposite_edge, HexCoord};
 pub use hex::Hex;
 pub use map::Map;
 pub use rail::Rail;
+pub use route::{Route, RouteSet};
 pub use stops::*;
 pub use tile::Tile;
 pub use tile_set::TileSet;
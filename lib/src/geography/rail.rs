@@ -1,13 +1,12 @@
 use super::{City, Location, Stop};
+use crate::toml_reader::{index_path, key_path, TomlReader};
 use crate::PubComId;
+use serde::{Deserialize, Serialize};
 use toml::Value;
 
-static EDGES_MISSING: &str = "edges is missing";
-static EDGES_TYPEERROR: &str = "edges is not of type Array";
-static EDGE_TYPEERROR: &str = "edge is not of type Integer";
 static ACTION_FORBIDDEN: &str = "action is forbidden";
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 ///Represents a rail
 pub struct Rail {
     edges: Vec<u32>,
@@ -15,21 +14,51 @@ pub struct Rail {
 }
 
 impl Rail {
-    pub(crate) fn from_toml(toml: &Value) -> Self {
-        let mut edges = Vec::new();
-        let edges_toml = toml.get("edges").expect(EDGES_MISSING);
-        for value in edges_toml.as_array().expect(EDGES_TYPEERROR) {
-            let edge = value.as_integer().expect(EDGE_TYPEERROR);
-            edges.push(edge as u32);
-        }
-        let city = toml.get("city").map(|c| Stop::City(City::from_toml(c)));
-        let location = toml
-            .get("location")
-            .map(|l| Stop::Location(Location::from_toml(l)));
-        Self {
-            edges,
-            stop: city.or(location),
-        }
+    pub(crate) fn from_toml(toml: &Value, path: &str, reader: &mut TomlReader) -> Option<Self> {
+        let edges_path = key_path(path, "edges");
+        let edges = reader.array(toml, path, "edges").and_then(|edges_toml| {
+            let mut edges = Vec::new();
+            for (i, value) in edges_toml.iter().enumerate() {
+                let edge_path = index_path(&edges_path, i);
+                match reader.as_unsigned(value, &edge_path) {
+                    Some(edge) if edge > 5 => {
+                        reader.error(&edge_path, format!("is not an edge: {}", edge));
+                    }
+                    Some(edge) if edges.contains(&edge) => {
+                        reader.error(&edge_path, format!("is a duplicate edge: {}", edge));
+                    }
+                    Some(edge) => edges.push(edge),
+                    None => (),
+                }
+            }
+            if edges_toml.is_empty() {
+                reader.error(&edges_path, "is empty".to_string());
+            }
+            if edges.len() == edges_toml.len() {
+                Some(edges)
+            } else {
+                None
+            }
+        });
+        let city = toml
+            .get("city")
+            .map(|c| City::from_toml(c, &key_path(path, "city"), reader).map(Stop::City));
+        let location = toml.get("location").map(|l| {
+            Location::from_toml(l, &key_path(path, "location"), reader).map(Stop::Location)
+        });
+        let stop = match (city, location) {
+            (Some(_), Some(_)) => {
+                reader.error(path, "has both a city and a location".to_string());
+                return None;
+            }
+            (Some(city), None) => Some(city?),
+            (None, Some(location)) => Some(location?),
+            (None, None) => None,
+        };
+        Some(Self {
+            edges: edges?,
+            stop,
+        })
     }
 
     pub(crate) fn place_station(&mut self, pub_com: PubComId) {
@@ -43,7 +72,33 @@ impl Rail {
                 }
             }
         } else {
-            panic!(ACTION_FORBIDDEN);
+            panic!("{}", ACTION_FORBIDDEN);
+        }
+    }
+
+    pub(crate) fn carry_over(&mut self, city: &City) {
+        if let Some(Stop::City(upgrade)) = &mut self.stop {
+            upgrade.carry_over(city);
+        } else {
+            unreachable!();
+        }
+    }
+
+    pub(crate) fn upgrades_to(&self, upgrade: &Rail) -> bool {
+        let stops_match = match (&self.stop, &upgrade.stop) {
+            (None, None) => true,
+            (Some(Stop::City(city)), Some(Stop::City(upgrade))) => {
+                (city.spots() == 0) == (upgrade.spots() == 0)
+            }
+            _ => false,
+        };
+        stops_match && self.edges.iter().all(|e| upgrade.edges.contains(e))
+    }
+
+    pub(crate) fn rotated(&self, rotation: u32) -> Self {
+        Self {
+            edges: self.edges.iter().map(|e| (e + rotation) % 6).collect(),
+            stop: self.stop.clone(),
         }
     }
 
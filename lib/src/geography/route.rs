@@ -0,0 +1,413 @@
+use super::{opposite_edge, Map, Stop};
+use crate::{PhaseId, PubComId, TrainId};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+/// Represents a route run by a train
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    train: TrainId,
+    stops: Vec<(usize, usize, usize)>,
+    revenue: u32,
+}
+
+impl Route {
+    /// Returns the train running this `Route`
+    pub fn train(&self) -> TrainId {
+        self.train
+    }
+
+    /// Returns the stops of this `Route` in order as `x`, `y` and stop index, numbering the stops
+    /// of a hex like the stations of `Map::stations`
+    pub fn stops(&self) -> &[(usize, usize, usize)] {
+        &self.stops
+    }
+
+    /// Returns the revenue of this `Route`
+    pub fn revenue(&self) -> u32 {
+        self.revenue
+    }
+}
+
+/// Represents the routes run by the trains of a public company
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RouteSet {
+    routes: Vec<Route>,
+}
+
+impl RouteSet {
+    /// Returns the total revenue of this `RouteSet`
+    pub fn revenue(&self) -> u32 {
+        self.routes.iter().map(|r| r.revenue).sum()
+    }
+
+    /// Returns the routes of this `RouteSet`
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Path {
+    stops: Vec<(usize, usize, usize)>,
+    sides: Vec<(usize, usize, u32)>,
+    revenue: u32,
+}
+
+impl Map {
+    /// Returns the revenue-maximising routes of `trains` run by `pub_com` on this `Map` during
+    /// `phase`
+    pub fn best_routes(&self, pub_com: PubComId, trains: &[TrainId], phase: PhaseId) -> RouteSet {
+        let max_stops = if trains.iter().any(|t| t.range().is_none()) {
+            None
+        } else {
+            trains.iter().filter_map(|t| t.range()).max().or(Some(0))
+        };
+        let paths = self.paths(pub_com, phase, max_stops);
+        let mut trains = trains.to_vec();
+        trains.sort_unstable_by(|t_1, t_2| t_2.cmp(t_1));
+        let candidates: Vec<Vec<&Path>> = trains
+            .iter()
+            .map(|t| {
+                let mut candidates: Vec<&Path> = paths
+                    .iter()
+                    .filter(|p| t.range().is_none_or(|r| p.stops.len() <= r))
+                    .collect();
+                candidates.sort_by_key(|p| Reverse(p.revenue));
+                candidates
+            })
+            .collect();
+        let mut bounds = vec![0; trains.len() + 1];
+        for i in (0..trains.len()).rev() {
+            bounds[i] = bounds[i + 1] + candidates[i].first().map_or(0, |p| p.revenue);
+        }
+        let mut best = (0, vec![None; trains.len()]);
+        let mut choice = Vec::with_capacity(trains.len());
+        Self::select(
+            &candidates,
+            &bounds,
+            &mut HashSet::new(),
+            0,
+            &mut choice,
+            &mut best,
+        );
+        RouteSet {
+            routes: best
+                .1
+                .iter()
+                .zip(&trains)
+                .filter_map(|(&c, &train)| {
+                    c.map(|(i, k)| Route {
+                        train,
+                        stops: candidates[i][k]
+                            .stops
+                            .iter()
+                            .map(|&(x, y, i)| (x, y, self.hex(x, y).unwrap().rail_stop(i)))
+                            .collect(),
+                        revenue: candidates[i][k].revenue,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn select(
+        candidates: &[Vec<&Path>],
+        bounds: &[u32],
+        used: &mut HashSet<(usize, usize, u32)>,
+        revenue: u32,
+        choice: &mut Vec<Option<(usize, usize)>>,
+        best: &mut (u32, Vec<Option<(usize, usize)>>),
+    ) {
+        let i = choice.len();
+        if i == candidates.len() {
+            if revenue > best.0 {
+                *best = (revenue, choice.clone());
+            }
+            return;
+        }
+        if revenue + bounds[i] <= best.0 {
+            return;
+        }
+        for (k, path) in candidates[i].iter().enumerate() {
+            if revenue + path.revenue + bounds[i + 1] <= best.0 {
+                break;
+            }
+            if path.sides.iter().any(|s| used.contains(s)) {
+                continue;
+            }
+            used.extend(path.sides.iter().copied());
+            choice.push(Some((i, k)));
+            Self::select(
+                candidates,
+                bounds,
+                used,
+                revenue + path.revenue,
+                choice,
+                best,
+            );
+            choice.pop();
+            for side in &path.sides {
+                used.remove(side);
+            }
+        }
+        choice.push(None);
+        Self::select(candidates, bounds, used, revenue, choice, best);
+        choice.pop();
+    }
+
+    /// Returns the paths that `pub_com` can run with at most `max_stops` stops. Paths are built
+    /// from the legs leading out of each station of `pub_com`, joining two legs where the station
+    /// can be passed through, so the search never starts away from its stations.
+    fn paths(&self, pub_com: PubComId, phase: PhaseId, max_stops: Option<usize>) -> Vec<Path> {
+        let mut paths = Vec::new();
+        if max_stops == Some(0) {
+            return paths;
+        }
+        let mut seen = HashSet::new();
+        for &(x, y, stop) in self.stations(pub_com) {
+            let hex = self.hex(x, y).unwrap();
+            let i = match hex.stop_rail(stop) {
+                Some(i) => i,
+                None => continue,
+            };
+            let stop = hex.rails()[i].stop().unwrap();
+            let mut start = Path::default();
+            Self::push_stop(&mut start, phase, (x, y, i), stop);
+            let mut legs = Vec::new();
+            self.extend(pub_com, phase, max_stops, &mut start, &mut legs);
+            for (k, leg) in legs.iter().enumerate() {
+                Self::insert_path(leg.clone(), &mut seen, &mut paths);
+                if let Stop::City(_) = stop {
+                    for other in &legs[k + 1..] {
+                        if let Some(path) = Self::join(&start, leg, other, max_stops) {
+                            Self::insert_path(path, &mut seen, &mut paths);
+                        }
+                    }
+                }
+            }
+        }
+        paths
+    }
+
+    /// Returns the path running `leg_1` backwards into `start` and on along `leg_2`, if the legs
+    /// share no stops or track and the path has at most `max_stops` stops
+    fn join(start: &Path, leg_1: &Path, leg_2: &Path, max_stops: Option<usize>) -> Option<Path> {
+        let stop_count = leg_1.stops.len() + leg_2.stops.len() - 1;
+        if max_stops.is_some_and(|m| stop_count > m)
+            || leg_1.stops[1..].iter().any(|s| leg_2.stops.contains(s))
+            || leg_1.sides.iter().any(|s| leg_2.sides.contains(s))
+        {
+            return None;
+        }
+        let mut stops = leg_1.stops.clone();
+        stops.reverse();
+        stops.extend_from_slice(&leg_2.stops[1..]);
+        let mut sides = leg_1.sides.clone();
+        sides.extend_from_slice(&leg_2.sides);
+        Some(Path {
+            stops,
+            sides,
+            revenue: leg_1.revenue + leg_2.revenue - start.revenue,
+        })
+    }
+
+    /// Adds `path` to `paths` unless it or its reverse has been seen before
+    fn insert_path(
+        path: Path,
+        seen: &mut HashSet<Vec<(usize, usize, usize)>>,
+        paths: &mut Vec<Path>,
+    ) {
+        let mut reversed = path.stops.clone();
+        reversed.reverse();
+        if seen.insert(path.stops.clone().min(reversed)) {
+            paths.push(path);
+        }
+    }
+
+    fn extend(
+        &self,
+        pub_com: PubComId,
+        phase: PhaseId,
+        max_stops: Option<usize>,
+        path: &mut Path,
+        paths: &mut Vec<Path>,
+    ) {
+        let (x, y, i) = *path.stops.last().unwrap();
+        let rail = &self.hex(x, y).unwrap().rails()[i];
+        if path.stops.len() > 1 {
+            paths.push(path.clone());
+            let passable = match rail.stop() {
+                Some(Stop::City(city)) => !city.blocks(pub_com),
+                _ => false,
+            };
+            if !passable {
+                return;
+            }
+        }
+        if max_stops.is_some_and(|m| path.stops.len() >= m) {
+            return;
+        }
+        for &edge in rail.edges() {
+            self.follow(pub_com, phase, max_stops, x, y, edge, path, paths);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn follow(
+        &self,
+        pub_com: PubComId,
+        phase: PhaseId,
+        max_stops: Option<usize>,
+        x: usize,
+        y: usize,
+        edge: u32,
+        path: &mut Path,
+        paths: &mut Vec<Path>,
+    ) {
+        let (nx, ny) = match self.neighbor(x, y, edge) {
+            Some(position) => position,
+            None => return,
+        };
+        let entry = opposite_edge(edge);
+        let side = (x, y, edge).min((nx, ny, entry));
+        if path.sides.contains(&side) {
+            return;
+        }
+        path.sides.push(side);
+        for (j, rail) in self.hex(nx, ny).unwrap().rails().iter().enumerate() {
+            if !rail.edges().contains(&entry) {
+                continue;
+            }
+            if let Some(stop) = rail.stop() {
+                if !path.stops.contains(&(nx, ny, j)) {
+                    let revenue = path.revenue;
+                    Self::push_stop(path, phase, (nx, ny, j), stop);
+                    self.extend(pub_com, phase, max_stops, path, paths);
+                    path.stops.pop();
+                    path.revenue = revenue;
+                }
+            } else {
+                for &e in rail.edges().iter().filter(|&&e| e != entry) {
+                    self.follow(pub_com, phase, max_stops, nx, ny, e, path, paths);
+                }
+            }
+        }
+        path.sides.pop();
+    }
+
+    fn push_stop(path: &mut Path, phase: PhaseId, position: (usize, usize, usize), stop: &Stop) {
+        path.stops.push(position);
+        path.revenue += match stop {
+            Stop::City(city) => city.value(),
+            Stop::Location(location) => location.value(phase),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A column of five gray cities worth 10, 20, 100, 20 and 10 with a station in the middle one
+    fn line_map() -> Map {
+        let mut toml = "width = 1\nheight = 5\nhomes = []\n".to_string();
+        for (y, (value, edges)) in [
+            (10, "[0]"),
+            (20, "[0, 3]"),
+            (100, "[0, 3]"),
+            (20, "[0, 3]"),
+            (10, "[3]"),
+        ]
+        .iter()
+        .enumerate()
+        {
+            toml.push_str(&format!(
+                "[[hexes]]\nx = 0\ny = {}\ncolor = \"Gray\"\nrails = [{{edges = {}, city = {{value = {}, spots = 1}}}}]\n",
+                y, edges, value
+            ));
+        }
+        let mut map = Map::from_toml("map.toml", &toml).unwrap();
+        map.place_station(PubComId::BAndO, 0, 2, 0);
+        map
+    }
+
+    fn sorted_stops(route_set: &RouteSet) -> Vec<Vec<(usize, usize, usize)>> {
+        let mut routes: Vec<_> = route_set
+            .routes()
+            .iter()
+            .map(|r| {
+                let mut stops = r.stops().to_vec();
+                stops.sort_unstable();
+                stops
+            })
+            .collect();
+        routes.sort_unstable();
+        routes
+    }
+
+    #[test]
+    fn best_single_route() {
+        let routes = line_map().best_routes(PubComId::BAndO, &[TrainId::Range3], PhaseId::Phase2);
+        assert_eq!(routes.revenue(), 140);
+        assert_eq!(
+            sorted_stops(&routes),
+            vec![vec![(0, 1, 0), (0, 2, 0), (0, 3, 0)]]
+        );
+    }
+
+    #[test]
+    fn best_pair_avoids_best_single_route() {
+        let trains = [TrainId::Range3, TrainId::Range3];
+        let routes = line_map().best_routes(PubComId::BAndO, &trains, PhaseId::Phase2);
+        assert_eq!(routes.revenue(), 260);
+        assert_eq!(
+            sorted_stops(&routes),
+            vec![
+                vec![(0, 0, 0), (0, 1, 0), (0, 2, 0)],
+                vec![(0, 2, 0), (0, 3, 0), (0, 4, 0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn routes_need_a_station() {
+        let routes = line_map().best_routes(PubComId::CAndO, &[TrainId::Range3], PhaseId::Phase2);
+        assert_eq!(routes.revenue(), 0);
+        assert!(routes.routes().is_empty());
+    }
+
+    #[test]
+    fn diesel_runs_the_whole_line() {
+        let routes = line_map().best_routes(PubComId::BAndO, &[TrainId::Diesel], PhaseId::Phase2);
+        assert_eq!(routes.revenue(), 160);
+        assert_eq!(routes.routes()[0].stops().len(), 5);
+    }
+
+    #[test]
+    fn route_stops_are_numbered_like_stations() {
+        let toml = r#"
+            width = 1
+            height = 2
+            homes = []
+
+            [[hexes]]
+            x = 0
+            y = 0
+            color = "Gray"
+            rails = [{edges = [2, 4]}, {edges = [0], city = {value = 10, spots = 1}}]
+
+            [[hexes]]
+            x = 0
+            y = 1
+            color = "Gray"
+            rails = [{edges = [3], city = {value = 20, spots = 1}}]
+        "#;
+        let mut map = Map::from_toml("map.toml", toml).unwrap();
+        map.place_station(PubComId::BAndO, 0, 0, 0);
+        let routes = map.best_routes(PubComId::BAndO, &[TrainId::Range2], PhaseId::Phase2);
+        assert_eq!(map.stations(PubComId::BAndO), &[(0, 0, 0)]);
+        assert_eq!(sorted_stops(&routes), vec![vec![(0, 0, 0), (0, 1, 0)]]);
+    }
+}
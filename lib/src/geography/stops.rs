@@ -1,20 +1,13 @@
-use crate::PubComId;
+use crate::toml_reader::{index_path, key_path, TomlReader};
+use crate::{PhaseId, PubComId};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use toml::Value;
 
-static VALUE_MISSING: &str = "value is missing";
-static VALUE_TYPEERROR: &str = "value is not of type Integer";
-static SPOTS_MISSING: &str = "spots is missing";
-static SPOTS_TYPEERROR: &str = "spots is not of type Integer";
-static VALUES_MISSING: &str = "values is missing";
-static VALUES_TYPEERROR: &str = "values is not of type Array";
-static VALUES_LENERROR: &str = "values is not of length four";
-static NAME_MISSING: &str = "name is missing";
-static NAME_TYPEERROR: &str = "name is not of type String";
 static ACTION_FORBIDDEN: &str = "action is forbidden";
 
 /// Represents a train stop
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Stop {
     /// Stop with a city
     City(City),
@@ -22,49 +15,84 @@ pub enum Stop {
     Location(Location),
 }
 
+impl Stop {
+    pub(crate) fn station_allowed(&self, pub_com: PubComId) -> bool {
+        match self {
+            Stop::City(city) => city.station_allowed(pub_com),
+            Stop::Location(location) => location.station.is_none(),
+        }
+    }
+}
+
 /// Represents a city
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct City {
     value: u32,
+    #[serde(serialize_with = "crate::sorted_serde::set")]
     stations: HashSet<PubComId>,
     spots: usize,
     name: Option<String>,
 }
 
 impl City {
-    pub(crate) fn from_toml(toml: &Value) -> Self {
-        let value = toml
-            .get("value")
-            .expect(VALUE_MISSING)
-            .as_integer()
-            .expect(VALUE_TYPEERROR) as u32;
-        let spots = toml
-            .get("spots")
-            .expect(SPOTS_MISSING)
-            .as_integer()
-            .expect(SPOTS_TYPEERROR) as usize;
-        let name = toml
-            .get("name")
-            .map(|n| n.as_str().expect(NAME_TYPEERROR).to_string());
-        Self {
-            value,
+    pub(crate) fn from_toml(toml: &Value, path: &str, reader: &mut TomlReader) -> Option<Self> {
+        let value = reader.unsigned(toml, path, "value");
+        let spots = reader.unsigned(toml, path, "spots");
+        let name = reader.opt_string(toml, path, "name");
+        Some(Self {
+            value: value?,
             stations: HashSet::new(),
-            spots,
-            name,
-        }
+            spots: spots? as usize,
+            name: name.map(|n| n.to_string()),
+        })
     }
 
     pub(crate) fn place_station(&mut self, pub_com: PubComId) {
-        if self.stations.len() < self.spots && !self.stations.contains(&pub_com) {
+        if self.station_allowed(pub_com) {
             self.stations.insert(pub_com);
         } else {
-            panic!(ACTION_FORBIDDEN);
+            panic!("{}", ACTION_FORBIDDEN);
+        }
+    }
+
+    pub(crate) fn carry_over(&mut self, city: &City) {
+        self.stations = city.stations.clone();
+        if self.name.is_none() {
+            self.name = city.name.clone();
         }
     }
+
+    pub(crate) fn blocks(&self, pub_com: PubComId) -> bool {
+        self.spots > 0 && self.stations.len() >= self.spots && !self.stations.contains(&pub_com)
+    }
+
+    pub(crate) fn station_allowed(&self, pub_com: PubComId) -> bool {
+        self.stations.len() < self.spots && !self.stations.contains(&pub_com)
+    }
+
+    /// Returns the value of this `City`
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// Returns the public companies with a station in this `City`
+    pub fn stations(&self) -> &HashSet<PubComId> {
+        &self.stations
+    }
+
+    /// Returns the number of station spots in this `City`
+    pub fn spots(&self) -> usize {
+        self.spots
+    }
+
+    /// Returns the name of this `City`, if any
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
 }
 
 /// Represents an off-board location
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Location {
     values: (u32, u32, u32, u32),
     station: Option<PubComId>,
@@ -72,38 +100,57 @@ pub struct Location {
 }
 
 impl Location {
-    pub(crate) fn from_toml(toml: &Value) -> Self {
-        let values = toml
-            .get("values")
-            .expect(VALUES_MISSING)
-            .as_array()
-            .expect(VALUES_TYPEERROR);
-        if values.len() != 4 {
-            panic!(VALUES_LENERROR);
-        }
-        let values = (
-            values[0].as_integer().expect(VALUE_TYPEERROR) as u32,
-            values[1].as_integer().expect(VALUE_TYPEERROR) as u32,
-            values[2].as_integer().expect(VALUE_TYPEERROR) as u32,
-            values[3].as_integer().expect(VALUE_TYPEERROR) as u32,
-        );
-        let name = toml
-            .get("name")
-            .expect(NAME_MISSING)
-            .as_str()
-            .expect(NAME_TYPEERROR);
-        Self {
-            values,
+    pub(crate) fn from_toml(toml: &Value, path: &str, reader: &mut TomlReader) -> Option<Self> {
+        let values_path = key_path(path, "values");
+        let values = reader.array(toml, path, "values").and_then(|values_toml| {
+            if values_toml.len() != 4 {
+                reader.error(&values_path, "is not of length four".to_string());
+                return None;
+            }
+            let mut values = Vec::new();
+            for (i, value) in values_toml.iter().enumerate() {
+                values.push(reader.as_unsigned(value, &index_path(&values_path, i)));
+            }
+            Some((values[0]?, values[1]?, values[2]?, values[3]?))
+        });
+        let name = reader.string(toml, path, "name");
+        Some(Self {
+            values: values?,
             station: None,
-            name: name.to_string(),
-        }
+            name: name?.to_string(),
+        })
     }
 
     pub(crate) fn place_station(&mut self, pub_com: PubComId) {
         if self.station.is_none() {
             self.station = Some(pub_com);
         } else {
-            panic!(ACTION_FORBIDDEN);
+            panic!("{}", ACTION_FORBIDDEN);
         }
     }
+
+    /// Returns the values of this `Location` during the yellow, green, brown and gray phases
+    pub fn values(&self) -> (u32, u32, u32, u32) {
+        self.values
+    }
+
+    /// Returns the value of this `Location` during `phase`
+    pub fn value(&self, phase: PhaseId) -> u32 {
+        match phase {
+            PhaseId::Phase2 => self.values.0,
+            PhaseId::Phase3 | PhaseId::Phase4 => self.values.1,
+            PhaseId::Phase5 | PhaseId::Phase6 => self.values.2,
+            PhaseId::PhaseD => self.values.3,
+        }
+    }
+
+    /// Returns the public company with a station in this `Location`, if any
+    pub fn station(&self) -> Option<PubComId> {
+        self.station
+    }
+
+    /// Returns the name of this `Location`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 }
@@ -1,16 +1,11 @@
 use super::Rail;
+use crate::toml_reader::{index_path, key_path, TomlReader};
 use crate::ColorId;
+use serde::{Deserialize, Serialize};
 use toml::Value;
 
-static RAILS_MISSING: &str = "rails is missing";
-static RAILS_TYPEERROR: &str = "rails is not of type Array";
-static COLOR_MISSING: &str = "color is missing";
-static COLOR_TYPEERROR: &str = "color is not of type String";
-static UPGRADES_TYPEERROR: &str = "upgrades is not of type Array";
-static UPGRADE_TYPEERROR: &str = "upgrade is not of type Integer";
-
 /// Represents a tile
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Tile {
     rails: Vec<Rail>,
     color: ColorId,
@@ -18,28 +13,77 @@ pub struct Tile {
 }
 
 impl Tile {
-    pub(crate) fn from_toml(toml: &Value) -> Self {
-        let mut rails = Vec::new();
-        let rails_toml = toml.get("rails").expect(RAILS_MISSING);
-        for value in rails_toml.as_array().expect(RAILS_TYPEERROR) {
-            rails.push(Rail::from_toml(value));
+    pub(crate) fn from_toml(toml: &Value, path: &str, reader: &mut TomlReader) -> Option<Self> {
+        let rails_path = key_path(path, "rails");
+        let rails = reader.array(toml, path, "rails").and_then(|rails_toml| {
+            let mut rails = Vec::new();
+            for (i, value) in rails_toml.iter().enumerate() {
+                rails.push(Rail::from_toml(value, &index_path(&rails_path, i), reader));
+            }
+            rails.into_iter().collect::<Option<Vec<_>>>()
+        });
+        let color = reader.parsed::<ColorId>(toml, path, "color");
+        let upgrades = Self::read_upgrades(toml, path, reader);
+        Some(Self {
+            rails: rails?,
+            color: color?,
+            upgrades: upgrades?,
+        })
+    }
+
+    pub(crate) fn read_upgrades(
+        toml: &Value,
+        path: &str,
+        reader: &mut TomlReader,
+    ) -> Option<Vec<i32>> {
+        let upgrades_path = key_path(path, "upgrades");
+        match reader.opt_array(toml, path, "upgrades") {
+            Some(upgrades_toml) => {
+                let mut upgrades = Vec::new();
+                for (i, value) in upgrades_toml.iter().enumerate() {
+                    let upgrade = reader.as_integer(value, &index_path(&upgrades_path, i));
+                    upgrades.push(upgrade.map(|u| u as i32));
+                }
+                upgrades.into_iter().collect::<Option<Vec<_>>>()
+            }
+            None if toml.get("upgrades").is_some() => None,
+            None => Some(Vec::new()),
+        }
+    }
+
+    /// Returns for each rail of this `Tile` the index of the rail of `upgrade` that it is carried
+    /// over to, or `None` if `upgrade` does not keep all rails of this `Tile`
+    pub(crate) fn rail_mapping(&self, upgrade: &Tile) -> Option<Vec<usize>> {
+        let mut mapping = Vec::with_capacity(self.rails.len());
+        if Self::map_rails(&self.rails, &upgrade.rails, &mut mapping) {
+            Some(mapping)
+        } else {
+            None
         }
-        let color = toml
-            .get("color")
-            .expect(COLOR_MISSING)
-            .as_str()
-            .expect(COLOR_TYPEERROR);
-        let mut upgrades = Vec::new();
-        if let Some(value) = toml.get("upgrades") {
-            for value in value.as_array().expect(UPGRADES_TYPEERROR) {
-                let upgrade = value.as_integer().expect(UPGRADE_TYPEERROR);
-                upgrades.push(upgrade as i32);
+    }
+
+    fn map_rails(rails: &[Rail], upgrade_rails: &[Rail], mapping: &mut Vec<usize>) -> bool {
+        if let Some(rail) = rails.get(mapping.len()) {
+            for (i, upgrade_rail) in upgrade_rails.iter().enumerate() {
+                if !mapping.contains(&i) && rail.upgrades_to(upgrade_rail) {
+                    mapping.push(i);
+                    if Self::map_rails(rails, upgrade_rails, mapping) {
+                        return true;
+                    }
+                    mapping.pop();
+                }
             }
+            false
+        } else {
+            true
         }
+    }
+
+    pub(crate) fn rotated(&self, rotation: u32) -> Self {
         Self {
-            rails,
-            color: color.parse::<ColorId>().unwrap(),
-            upgrades,
+            rails: self.rails.iter().map(|r| r.rotated(rotation)).collect(),
+            color: self.color,
+            upgrades: self.upgrades.clone(),
         }
     }
 
@@ -51,4 +95,14 @@ impl Tile {
     pub fn rails(&self) -> &[Rail] {
         &self.rails
     }
+
+    /// Returns the color of this `Tile`
+    pub fn color(&self) -> ColorId {
+        self.color
+    }
+
+    /// Returns the ids of the tiles this `Tile` can be upgraded to
+    pub fn upgrades(&self) -> &[i32] {
+        &self.upgrades
+    }
 }
@@ -1,43 +1,105 @@
 use super::Tile;
-use crate::INVALID_TOML;
+use crate::toml_reader::{index_path, key_path, TomlReader};
+use crate::ConfigError;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use toml::Value;
 
-static TILES_MISSING: &str = "tiles is missing";
-static TILES_TYPEERROR: &str = "tiles is not of type Array";
-static ID_MISSING: &str = "id is missing";
-static ID_TYPEERROR: &str = "id is not of type Integer";
-static COUNT_MISSING: &str = "count is missing";
-static COUNT_TYPEERROR: &str = "count is not of type Integer";
-
 /// Represents the tiles and their respective counts avaliable in a game
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TileSet {
+    #[serde(serialize_with = "crate::sorted_serde::map")]
     tiles: HashMap<i32, (Tile, i32)>,
 }
 
 impl TileSet {
-    pub(crate) fn from_toml(s: &str) -> Self {
-        let toml = s.parse::<Value>().expect(INVALID_TOML);
+    /// Returns the `TileSet` described by the TOML in `s`, naming `file` in any `ConfigError`
+    pub fn from_toml(file: &str, s: &str) -> Result<Self, Vec<ConfigError>> {
+        let mut reader = TomlReader::new(file);
+        let tile_set = reader
+            .parse(s)
+            .and_then(|toml| Self::read(&toml, &mut reader));
+        reader.finish(tile_set)
+    }
+
+    fn read(toml: &Value, reader: &mut TomlReader) -> Option<Self> {
         let mut tiles = HashMap::new();
-        let tiles_toml = toml.get("tiles").expect(TILES_MISSING);
-        for value in tiles_toml.as_array().expect(TILES_TYPEERROR) {
-            let id = value
-                .get("id")
-                .expect(ID_MISSING)
-                .as_integer()
-                .expect(ID_TYPEERROR) as i32;
-            if tiles.contains_key(&id) {
-                panic!("tile with id={} already exists", id);
+        let mut paths = HashMap::new();
+        let mut valid = true;
+        for (i, value) in reader.array(toml, "", "tiles")?.iter().enumerate() {
+            let path = index_path("tiles", i);
+            let id = reader.integer(value, &path, "id").map(|id| id as i32);
+            let count = match reader.integer(value, &path, "count") {
+                Some(count) if count < -1 => {
+                    reader.error(&key_path(&path, "count"), format!("is negative: {}", count));
+                    None
+                }
+                count => count.map(|c| c as i32),
+            };
+            let tile = Tile::from_toml(value, &path, reader);
+            if let (Some(id), Some(count), Some(tile)) = (id, count, tile) {
+                if tiles.contains_key(&id) {
+                    reader.error(&path, format!("is a duplicate tile with id={}", id));
+                    valid = false;
+                }
+                tiles.insert(id, (tile, count));
+                paths.insert(id, path);
+            } else {
+                valid = false;
             }
-            let count = value
-                .get("count")
-                .expect(COUNT_MISSING)
-                .as_integer()
-                .expect(COUNT_TYPEERROR);
-            let tile = Tile::from_toml(value);
-            tiles.insert(id, (tile, count as i32));
         }
-        Self { tiles }
+        if !valid {
+            return None;
+        }
+        for (id, (tile, _)) in &tiles {
+            for upgrade in tile.upgrades() {
+                match tiles.get(upgrade) {
+                    None => {
+                        reader.error(&paths[id], format!("upgrades to missing tile {}", upgrade));
+                        valid = false;
+                    }
+                    Some((upgrade_tile, _)) if upgrade_tile.color() <= tile.color() => {
+                        reader.error(
+                            &paths[id],
+                            format!("upgrades to non-later tile {}", upgrade),
+                        );
+                        valid = false;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        if valid {
+            Some(Self { tiles })
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn take_tile(&mut self, id: i32) -> Tile {
+        let (tile, count) = self.tiles.get_mut(&id).unwrap();
+        if *count > 0 {
+            *count -= 1;
+        }
+        tile.clone()
+    }
+
+    pub(crate) fn return_tile(&mut self, id: i32) {
+        let (_, count) = self.tiles.get_mut(&id).unwrap();
+        if *count >= 0 {
+            *count += 1;
+        }
+    }
+
+    /// Returns the ids of the tiles in this `TileSet` in ascending order
+    pub fn ids(&self) -> Vec<i32> {
+        let mut ids: Vec<i32> = self.tiles.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Returns the tile with `id` and its remaining count in this `TileSet`, if any
+    pub fn tile(&self, id: i32) -> Option<(&Tile, i32)> {
+        self.tiles.get(&id).map(|(tile, count)| (tile, *count))
     }
 }
@@ -19,4 +19,21 @@ impl TrackLayMap {
             .and_modify(|v| v.push(value))
             .or_insert_with(|| vec![value]);
     }
+
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(usize, usize, i32, u32) -> bool) {
+        for (&(x, y), track_lays) in &mut self.track_lays {
+            track_lays.retain(|&(tile, rotation)| f(x, y, tile, rotation));
+        }
+        self.track_lays.retain(|_, v| !v.is_empty());
+    }
+
+    /// Returns the positions with possible track lays as `x`, `y`
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.track_lays.keys().copied()
+    }
+
+    /// Returns the possible track lays at `x`, `y` as pairs of tile id and rotation
+    pub fn track_lays(&self, x: usize, y: usize) -> &[(i32, u32)] {
+        self.track_lays.get(&(x, y)).map_or(&[], |v| v)
+    }
 }
@@ -1,11 +1,14 @@
 use crate::rounds::{OperatingRound, PrivAuction, StockRound};
+use crate::EndReason;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::iter::Copied;
+use std::mem;
 use std::slice::Iter;
 use std::str::FromStr;
 
 /// Represents the color of a tile
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ColorId {
     /// Yellow tile color
     Yellow,
@@ -19,6 +22,19 @@ pub enum ColorId {
     Red,
 }
 
+impl ColorId {
+    /// Returns the color that tiles of this `ColorId` are upgraded to, if any
+    pub fn upgraded(self) -> Option<ColorId> {
+        match self {
+            ColorId::Yellow => Some(ColorId::Green),
+            ColorId::Green => Some(ColorId::Brown),
+            ColorId::Brown => Some(ColorId::Gray),
+            ColorId::Gray => None,
+            ColorId::Red => None,
+        }
+    }
+}
+
 impl FromStr for ColorId {
     type Err = String;
 
@@ -35,7 +51,7 @@ impl FromStr for ColorId {
 }
 
 /// Identifies a public company
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum PubComId {
     /// Baltimore and Ohio Railroad
     BAndO,
@@ -135,7 +151,7 @@ impl FromStr for PubComId {
 }
 
 /// Identifies a private company
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum PrivComId {
     /// Delaware and Raritan Canal
     DAndR(u32),
@@ -188,6 +204,12 @@ impl PrivComId {
         }
     }
 
+    /// Returns whether this `PrivComId` and `other` identify the same private company, ignoring
+    /// the cost of the D&R
+    pub fn same_as(self, other: Self) -> bool {
+        mem::discriminant(&self) == mem::discriminant(&other)
+    }
+
     /// Returns an iterator over the possible values of `PrivComId`
     pub fn values() -> Copied<Iter<'static, Self>> {
         [
@@ -233,9 +255,10 @@ impl FromStr for PrivComId {
 }
 
 /// Represents the terrain of a hex
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TerrainId {
     /// Plain hex terrain
+    #[default]
     Plain,
     /// River hex terrain
     River,
@@ -254,12 +277,6 @@ impl TerrainId {
     }
 }
 
-impl Default for TerrainId {
-    fn default() -> Self {
-        TerrainId::Plain
-    }
-}
-
 impl FromStr for TerrainId {
     type Err = String;
 
@@ -273,7 +290,7 @@ impl FromStr for TerrainId {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 /// Represents a train
 pub enum TrainId {
     /// 2 range train
@@ -290,6 +307,57 @@ pub enum TrainId {
     Diesel,
 }
 
+impl TrainId {
+    /// Returns the cost of this `TrainId`
+    pub fn cost(self) -> u32 {
+        match self {
+            TrainId::Range2 => 80,
+            TrainId::Range3 => 180,
+            TrainId::Range4 => 300,
+            TrainId::Range5 => 500,
+            TrainId::Range6 => 630,
+            TrainId::Diesel => 900,
+        }
+    }
+
+    /// Returns the phase started by the first purchase of a train of this `TrainId`
+    pub fn phase(self) -> PhaseId {
+        match self {
+            TrainId::Range2 => PhaseId::Phase2,
+            TrainId::Range3 => PhaseId::Phase3,
+            TrainId::Range4 => PhaseId::Phase4,
+            TrainId::Range5 => PhaseId::Phase5,
+            TrainId::Range6 => PhaseId::Phase6,
+            TrainId::Diesel => PhaseId::PhaseD,
+        }
+    }
+
+    /// Returns the number of stops a train of this `TrainId` may visit, or `None` if unlimited
+    pub fn range(self) -> Option<usize> {
+        match self {
+            TrainId::Range2 => Some(2),
+            TrainId::Range3 => Some(3),
+            TrainId::Range4 => Some(4),
+            TrainId::Range5 => Some(5),
+            TrainId::Range6 => Some(6),
+            TrainId::Diesel => None,
+        }
+    }
+}
+
+impl Display for TrainId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            TrainId::Range2 => write!(f, "2"),
+            TrainId::Range3 => write!(f, "3"),
+            TrainId::Range4 => write!(f, "4"),
+            TrainId::Range5 => write!(f, "5"),
+            TrainId::Range6 => write!(f, "6"),
+            TrainId::Diesel => write!(f, "D"),
+        }
+    }
+}
+
 impl FromStr for TrainId {
     type Err = String;
 
@@ -306,7 +374,7 @@ impl FromStr for TrainId {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 /// Represents a game phase
 pub enum PhaseId {
     /// 2 train phase
@@ -332,9 +400,39 @@ impl PhaseId {
             _ => 3,
         }
     }
+
+    /// Returns the number of trains a public company may own during this `PhaseId`
+    pub fn train_limit(self) -> usize {
+        match self {
+            PhaseId::Phase2 | PhaseId::Phase3 => 4,
+            PhaseId::Phase4 => 3,
+            _ => 2,
+        }
+    }
+
+    /// Returns the trains that rust when this `PhaseId` starts, if any
+    pub fn rusted_train(self) -> Option<TrainId> {
+        match self {
+            PhaseId::Phase4 => Some(TrainId::Range2),
+            PhaseId::Phase6 => Some(TrainId::Range3),
+            PhaseId::PhaseD => Some(TrainId::Range4),
+            _ => None,
+        }
+    }
+
+    /// Returns whether tiles of `color` may be laid during this `PhaseId`
+    pub fn allows_color(self, color: ColorId) -> bool {
+        match color {
+            ColorId::Yellow => true,
+            ColorId::Green => self >= PhaseId::Phase3,
+            ColorId::Brown => self >= PhaseId::Phase5,
+            ColorId::Gray => self >= PhaseId::PhaseD,
+            ColorId::Red => false,
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// Represents a game round
 pub enum RoundId {
     /// Private auction
@@ -343,9 +441,11 @@ pub enum RoundId {
     StockRound(StockRound),
     /// Operating round
     OperatingRound(OperatingRound),
+    /// The game is over
+    GameOver(EndReason),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 /// Represents a game phase
 pub enum OperatingPhaseId {
     /// Lay track phase
@@ -0,0 +1,330 @@
+use crate::{Action, Game, PrivComId, PubComId, RoundId, TrainId};
+use serde::{Deserialize, Serialize};
+
+/// Represents a set of legal moves by the current player of a game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LegalAction {
+    /// Performs the action
+    Action(Action),
+    /// Bids any amount from `min` to `max` on `private`
+    BidPriv {
+        /// The private company bid on
+        private: PrivComId,
+        /// The lowest amount allowed
+        min: u32,
+        /// The highest amount allowed
+        max: u32,
+    },
+    /// Buys `train` from `seller` for any price from `min` to `max`
+    BuyPubComTrain {
+        /// The selling public company
+        seller: PubComId,
+        /// The train
+        train: TrainId,
+        /// The lowest price allowed
+        min: u32,
+        /// The highest price allowed
+        max: u32,
+    },
+    /// Buys `private` from the player owning it for any price from `min` to `max`
+    BuyPriv {
+        /// The private company
+        private: PrivComId,
+        /// The lowest price allowed
+        min: u32,
+        /// The highest price allowed
+        max: u32,
+    },
+}
+
+impl LegalAction {
+    /// Returns whether `action` is one of the moves of this `LegalAction`
+    pub fn contains(&self, action: Action) -> bool {
+        match (*self, action) {
+            (LegalAction::Action(legal_action), action) => legal_action == action,
+            (
+                LegalAction::BidPriv { private, min, max },
+                Action::BidPriv {
+                    private: bid_private,
+                    amount,
+                },
+            ) => private == bid_private && (min..=max).contains(&amount),
+            (
+                LegalAction::BuyPubComTrain {
+                    seller,
+                    train,
+                    min,
+                    max,
+                },
+                Action::BuyPubComTrain {
+                    seller: buy_seller,
+                    train: buy_train,
+                    price,
+                },
+            ) => seller == buy_seller && train == buy_train && (min..=max).contains(&price),
+            (
+                LegalAction::BuyPriv { private, min, max },
+                Action::BuyPriv {
+                    private: buy_private,
+                    price,
+                },
+            ) => private == buy_private && (min..=max).contains(&price),
+            _ => false,
+        }
+    }
+}
+
+impl Game {
+    /// Returns every legal move of the current player
+    pub fn legal_actions(&self) -> Vec<LegalAction> {
+        let mut legal_actions = Vec::new();
+        let mut push = |action| {
+            if self.action_allowed(action) {
+                legal_actions.push(LegalAction::Action(action));
+            }
+        };
+        push(Action::Pass);
+        match &self.round {
+            RoundId::PrivAuction(_) => push(Action::BuyCheapestPriv),
+            RoundId::StockRound(_) => {
+                let current_player = &self.players[self.current_player];
+                for pub_com in PubComId::values() {
+                    push(Action::BuyIpoShare(pub_com));
+                    push(Action::BuyPoolShare(pub_com));
+                    for &par in self.par_track.values() {
+                        push(Action::BuyPresidency { pub_com, par });
+                        push(Action::SetPar { pub_com, par });
+                    }
+                    for count in 1..=current_player.shares().count(pub_com) {
+                        push(Action::SellShares { pub_com, count });
+                    }
+                }
+                push(Action::EndTurn);
+            }
+            RoundId::OperatingRound(_) => {
+                if let Some(track_lay_map) = self.track_lay_map() {
+                    for (x, y) in track_lay_map.positions() {
+                        for &(tile, rotation) in track_lay_map.track_lays(x, y) {
+                            push(Action::LayTile {
+                                x,
+                                y,
+                                tile,
+                                rotation,
+                            });
+                        }
+                    }
+                }
+                for coord in self.map.coords() {
+                    let (x, y) = (coord.x(), coord.y());
+                    for city in 0..self.map.hex(x, y).unwrap().stop_count() {
+                        push(Action::PlaceStation { x, y, city });
+                    }
+                }
+                push(Action::RunTrains { payout: true });
+                push(Action::RunTrains { payout: false });
+                push(Action::BuyTrain);
+                let mut market = self.train_set.market().to_vec();
+                market.sort_unstable();
+                market.dedup();
+                for train in market {
+                    push(Action::BuyMarketTrain(train));
+                }
+                push(Action::DeclareBankruptcy);
+                let pub_com = &self.pub_coms[&self.operating_pub_com().unwrap()];
+                for &private in pub_com.priv_coms() {
+                    for x in 0..self.map.width() {
+                        for y in 0..self.map.height() {
+                            if self.map.hex(x, y).and_then(|h| h.private()) != Some(private) {
+                                continue;
+                            }
+                            for tile in self.tile_set.ids() {
+                                for rotation in 0..6 {
+                                    push(Action::LayPrivTile {
+                                        private,
+                                        x,
+                                        y,
+                                        tile,
+                                        rotation,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            RoundId::GameOver(_) => (),
+        }
+        legal_actions.extend(self.legal_bids());
+        legal_actions.extend(self.legal_pub_com_train_buys());
+        legal_actions.extend(self.legal_priv_buys());
+        legal_actions
+    }
+
+    fn legal_bids(&self) -> Vec<LegalAction> {
+        let mut legal_bids = Vec::new();
+        if let RoundId::PrivAuction(priv_auction) = &self.round {
+            if let Some(current_priv) = priv_auction.current() {
+                let current_player = &self.players[self.current_player];
+                let bids = priv_auction.bids(current_player);
+                let privates = PrivComId::values()
+                    .filter(|&p| priv_auction.is_upcoming(p))
+                    .chain(Some(current_priv));
+                for private in privates {
+                    let min = priv_auction.max_bid(private) + 5;
+                    let other_bids: u32 = bids
+                        .iter()
+                        .filter(|&(&p, _)| p != private)
+                        .map(|(_, &bid)| bid)
+                        .sum();
+                    let max = current_player.capital().saturating_sub(other_bids);
+                    if min <= max
+                        && self.bid_priv_allowed(private, min)
+                        && self.bid_priv_allowed(private, max)
+                    {
+                        legal_bids.push(LegalAction::BidPriv { private, min, max });
+                    }
+                }
+            }
+        }
+        legal_bids
+    }
+
+    fn legal_pub_com_train_buys(&self) -> Vec<LegalAction> {
+        let mut legal_buys = Vec::new();
+        if let Some(pub_com) = self.operating_pub_com() {
+            let max = self.pub_coms[&pub_com].capital();
+            for seller in PubComId::values() {
+                let mut trains = self
+                    .pub_coms
+                    .get(&seller)
+                    .map_or(Vec::new(), |s| s.trains().to_vec());
+                trains.sort_unstable();
+                trains.dedup();
+                for train in trains {
+                    if self.buy_pub_com_train_allowed(seller, train, 1)
+                        && self.buy_pub_com_train_allowed(seller, train, max)
+                    {
+                        legal_buys.push(LegalAction::BuyPubComTrain {
+                            seller,
+                            train,
+                            min: 1,
+                            max,
+                        });
+                    }
+                }
+            }
+        }
+        legal_buys
+    }
+
+    fn legal_priv_buys(&self) -> Vec<LegalAction> {
+        let mut legal_buys = Vec::new();
+        if let Some(pub_com) = self.operating_pub_com() {
+            let capital = self.pub_coms[&pub_com].capital();
+            for player in &self.players {
+                let mut privates: Vec<_> = player.priv_coms().iter().copied().collect();
+                privates.sort_by_key(|p| p.cost());
+                for private in privates {
+                    let (min, max) = self.priv_price_range(private);
+                    let max = max.min(capital);
+                    if min <= max
+                        && self.buy_priv_allowed(private, min)
+                        && self.buy_priv_allowed(private, max)
+                    {
+                        legal_buys.push(LegalAction::BuyPriv { private, min, max });
+                    }
+                }
+            }
+        }
+        legal_buys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LegalAction;
+    use crate::game::tests;
+    use crate::{Action, PrivComId, PubComId, TrainId};
+
+    #[test]
+    fn legal_actions_in_priv_auction() {
+        let game = tests::game(4);
+        let legal_actions = game.legal_actions();
+        assert!(legal_actions.contains(&LegalAction::Action(Action::BuyCheapestPriv)));
+        assert!(legal_actions.contains(&LegalAction::BidPriv {
+            private: PrivComId::CToP,
+            min: 45,
+            max: 600
+        }));
+        assert!(legal_actions.contains(&LegalAction::BidPriv {
+            private: PrivComId::CV,
+            min: 205,
+            max: 600
+        }));
+        assert!(!legal_actions.iter().any(|a| matches!(
+            a,
+            LegalAction::BidPriv {
+                private: PrivComId::DAndR(_),
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn legal_actions_in_operating_round() {
+        let mut game = tests::game(4);
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.enter_operating_round(0);
+        let legal_actions = game.legal_actions();
+        let lay_tile = |rotation| {
+            LegalAction::Action(Action::LayTile {
+                x: 5,
+                y: 0,
+                tile: 57,
+                rotation,
+            })
+        };
+        assert!(legal_actions.contains(&lay_tile(1)));
+        assert!(!legal_actions.contains(&lay_tile(0)));
+        assert!(legal_actions.contains(&LegalAction::Action(Action::Pass)));
+        assert!(!legal_actions.contains(&LegalAction::Action(Action::BuyTrain)));
+    }
+
+    #[test]
+    fn legal_actions_contain_actions_in_their_range() {
+        let bid = LegalAction::BidPriv {
+            private: PrivComId::CToP,
+            min: 45,
+            max: 600,
+        };
+        let bid_action = |amount| Action::BidPriv {
+            private: PrivComId::CToP,
+            amount,
+        };
+        assert!(bid.contains(bid_action(45)));
+        assert!(bid.contains(bid_action(600)));
+        assert!(!bid.contains(bid_action(40)));
+        assert!(!bid.contains(Action::BidPriv {
+            private: PrivComId::CV,
+            amount: 200
+        }));
+        let buy = LegalAction::BuyPubComTrain {
+            seller: PubComId::PRR,
+            train: TrainId::Range2,
+            min: 1,
+            max: 100,
+        };
+        assert!(buy.contains(Action::BuyPubComTrain {
+            seller: PubComId::PRR,
+            train: TrainId::Range2,
+            price: 100
+        }));
+        assert!(!buy.contains(Action::BuyPubComTrain {
+            seller: PubComId::PRR,
+            train: TrainId::Range2,
+            price: 101
+        }));
+        assert!(LegalAction::Action(Action::Pass).contains(Action::Pass));
+        assert!(!LegalAction::Action(Action::Pass).contains(Action::EndTurn));
+    }
+}
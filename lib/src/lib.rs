@@ -6,15 +6,33 @@
 //! Game logic for the [18xx](https://boardgamegeek.com/boardgamefamily/19/18xx) board game
 //! [18Chesapeake](https://boardgamegeek.com/boardgame/253608/18chesapeake).
 
+mod action;
 pub mod economy;
+mod end_game;
+mod error;
+mod float;
 mod game;
+mod game_config;
 pub mod geography;
 mod ids;
+mod legal_action;
+mod priv_coms;
+pub mod render;
 pub mod rounds;
+mod snapshot;
+mod sorted_serde;
+mod toml_reader;
 mod train_set;
+mod undo;
 
+pub use action::Action;
+pub use end_game::{EndReason, Standing};
+pub use error::*;
+pub use float::{Capitalization, PresidencySource};
 pub use game::*;
+pub use game_config::GameConfig;
 pub use ids::*;
+pub use legal_action::LegalAction;
+pub use snapshot::SNAPSHOT_VERSION;
 pub use train_set::TrainSet;
-
-pub(crate) static INVALID_TOML: &str = "TOML is invalid";
+pub use undo::UndoPolicy;
@@ -0,0 +1,392 @@
+use crate::geography::Hex;
+use crate::{ActionError, Game, PhaseId, PrivComId, PubComId};
+use std::collections::{HashMap, HashSet};
+
+impl Game {
+    /// Returns the public company a share of which comes with Cornelius Vanderbilt
+    pub fn cv_pub_com(&self) -> PubComId {
+        self.cv_pub_com
+    }
+
+    /// Returns whether `hex` is reserved by a private company owned by a player, so that no tile
+    /// may be laid there
+    pub fn hex_blocked(&self, hex: &Hex) -> bool {
+        hex.private().is_some_and(|private| {
+            self.players
+                .iter()
+                .any(|p| p.priv_coms().iter().any(|p| p.same_as(private)))
+        })
+    }
+
+    /// Returns the lowest and highest price a public company may pay a player for `private`,
+    /// half and twice its cost, or what the owning player paid for the D&R
+    pub fn priv_price_range(&self, private: PrivComId) -> (u32, u32) {
+        let private = self.player_owned_priv(private).unwrap_or(private);
+        ((private.cost() / 2).max(1), private.cost() * 2)
+    }
+
+    /// Returns whether buying `private` from the player owning it for `price` is allowed
+    pub fn buy_priv_allowed(&self, private: PrivComId, price: u32) -> bool {
+        self.check_buy_priv(private, price).is_ok()
+    }
+
+    /// Returns `Ok` if buying `private` from the player owning it for `price` is allowed,
+    /// otherwise the reason it is not
+    pub fn check_buy_priv(&self, private: PrivComId, price: u32) -> Result<(), ActionError> {
+        let pub_com = &self.pub_coms[&self.operating_pub_com().ok_or(ActionError::WrongRound)?];
+        let (min, max) = self.priv_price_range(private);
+        if self.phase < PhaseId::Phase3 {
+            Err(ActionError::WrongPhase)
+        } else if private == PrivComId::BAndO || self.player_owned_priv(private).is_none() {
+            Err(ActionError::PrivateUnavailable)
+        } else if !(min..=max).contains(&price) {
+            Err(ActionError::InvalidPrice)
+        } else if pub_com.capital() < price {
+            Err(ActionError::InsufficientCapital)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Buys `private` from the player owning it for `price`, after which its revenue is paid to
+    /// the operating public company and its ability may be used by it
+    pub fn buy_priv(&mut self, private: PrivComId, price: u32) -> Result<(), ActionError> {
+        self.check_buy_priv(private, price)?;
+        let owned = self.player_owned_priv(private).unwrap();
+        let seller = self
+            .players
+            .iter_mut()
+            .find(|p| p.priv_coms().contains(&owned))
+            .unwrap();
+        seller.remove_priv(owned);
+        seller.add_capital(price);
+        let pub_com = self.operating_pub_com().unwrap();
+        let pub_com = self.pub_coms.get_mut(&pub_com).unwrap();
+        pub_com.remove_capital(price);
+        pub_com.add_priv(owned);
+        Ok(())
+    }
+
+    /// Returns the variant of `private` owned by a player, which for the D&R carries the price it
+    /// was bought for, if any
+    fn player_owned_priv(&self, private: PrivComId) -> Option<PrivComId> {
+        self.players
+            .iter()
+            .flat_map(|p| p.priv_coms())
+            .copied()
+            .find(|p| p.same_as(private))
+    }
+
+    /// Returns whether laying tile `tile` rotated `rotation` steps clockwise at `x`, `y` using the
+    /// ability of `private` is allowed
+    pub fn lay_priv_tile_allowed(
+        &self,
+        private: PrivComId,
+        x: usize,
+        y: usize,
+        tile: i32,
+        rotation: u32,
+    ) -> bool {
+        self.check_lay_priv_tile(private, x, y, tile, rotation)
+            .is_ok()
+    }
+
+    /// Returns `Ok` if laying tile `tile` rotated `rotation` steps clockwise at `x`, `y` using the
+    /// ability of `private` is allowed, otherwise the reason it is not
+    pub fn check_lay_priv_tile(
+        &self,
+        private: PrivComId,
+        x: usize,
+        y: usize,
+        tile: i32,
+        rotation: u32,
+    ) -> Result<(), ActionError> {
+        let pub_com = &self.pub_coms[&self.operating_pub_com().ok_or(ActionError::WrongRound)?];
+        if !pub_com.priv_coms().iter().any(|p| p.same_as(private)) {
+            return Err(ActionError::PrivateNotOwned);
+        }
+        let connected = match private {
+            PrivComId::CAndO => false,
+            PrivComId::BAndS => true,
+            _ => return Err(ActionError::NoAbility),
+        };
+        let hex = self.map.hex(x, y).ok_or(ActionError::InvalidHex)?;
+        let (new_tile, count) = self
+            .tile_set
+            .tile(tile)
+            .ok_or(ActionError::TileUnavailable)?;
+        if hex.private() != Some(private) {
+            Err(ActionError::HexNotReserved)
+        } else if rotation > 5 {
+            Err(ActionError::InvalidRotation)
+        } else if count == 0 {
+            Err(ActionError::TileUnavailable)
+        } else if !self.phase.allows_color(new_tile.color()) {
+            Err(ActionError::ColorUnavailable)
+        } else {
+            let new_tile = new_tile.rotated(rotation);
+            hex.check_lay_tile(tile, &new_tile)?;
+            let reach = if connected {
+                self.map.reachable_edges(pub_com.id())
+            } else {
+                let mut reach = HashMap::new();
+                reach.insert((x, y), (0..6).collect::<HashSet<_>>());
+                reach
+            };
+            self.map.check_track(&reach, pub_com.id(), x, y, &new_tile)
+        }
+    }
+
+    /// Lays tile `tile` rotated `rotation` steps clockwise at `x`, `y` using the ability of
+    /// `private`, without paying the terrain cost of the hex, and closes `private`
+    ///
+    /// The C&O lets the owning public company lay a tile in its reserved hex without connecting to
+    /// its track, while the B&S lets it lay a connected tile in one of its reserved hexes.
+    pub fn lay_priv_tile(
+        &mut self,
+        private: PrivComId,
+        x: usize,
+        y: usize,
+        tile: i32,
+        rotation: u32,
+    ) -> Result<(), ActionError> {
+        self.check_lay_priv_tile(private, x, y, tile, rotation)?;
+        if let Some(old_tile) = self.map.hex(x, y).unwrap().tile_id() {
+            self.tile_set.return_tile(old_tile);
+        }
+        let new_tile = self.tile_set.take_tile(tile).rotated(rotation);
+        self.map.lay_tile(x, y, tile, new_tile);
+        self.close_priv(private);
+        Ok(())
+    }
+
+    /// Gives `player` `private` for `price`, along with the presidency of the B&O for the B&O
+    /// and a share of the CV public company for Cornelius Vanderbilt
+    pub(crate) fn sell_priv(&mut self, player: usize, private: PrivComId, price: u32) {
+        let player = &mut self.players[player];
+        player.buy_priv(private, price);
+        self.bank.deposit(price);
+        match private {
+            PrivComId::BAndO => {
+                self.ipo.remove_presidency(PubComId::BAndO);
+                player.shares_mut().add_shares(PubComId::BAndO, 2);
+                player.shares_mut().add_presidency(PubComId::BAndO);
+            }
+            PrivComId::CV => {
+                self.ipo.remove_shares(self.cv_pub_com, 1);
+                player.shares_mut().add_shares(self.cv_pub_com, 1);
+            }
+            _ => (),
+        }
+    }
+
+    /// Closes the private companies whose closing is triggered by `pub_com` buying a train, the
+    /// B&O by the B&O and Cornelius Vanderbilt by the CV public company
+    pub(crate) fn close_privs_on_train(&mut self, pub_com: PubComId) {
+        if pub_com == PubComId::BAndO {
+            self.close_priv(PrivComId::BAndO);
+        }
+        if pub_com == self.cv_pub_com {
+            self.close_priv(PrivComId::CV);
+        }
+    }
+
+    /// Closes every private company once `phase` has started
+    pub(crate) fn close_privs_on_phase(&mut self, phase: PhaseId) {
+        if phase >= PhaseId::Phase5 {
+            for private in PrivComId::values() {
+                self.close_priv(private);
+            }
+        }
+    }
+
+    pub(crate) fn close_priv(&mut self, private: PrivComId) {
+        for player in &mut self.players {
+            player.remove_priv(private);
+        }
+        for pub_com in self.pub_coms.values_mut() {
+            pub_com.remove_priv(private);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::tests;
+    use crate::{ActionError, Game, PhaseId, PrivComId, PubComId};
+
+    fn operating_round_game(private: PrivComId) -> Game {
+        let mut game = tests::game(4);
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.pub_coms
+            .get_mut(&PubComId::PRR)
+            .unwrap()
+            .add_priv(private);
+        game.enter_operating_round(0);
+        game
+    }
+
+    fn owned(game: &Game, private: PrivComId) -> bool {
+        game.players
+            .iter()
+            .any(|p| p.priv_coms().contains(&private))
+            || game
+                .pub_coms
+                .values()
+                .any(|p| p.priv_coms().contains(&private))
+    }
+
+    #[test]
+    fn sell_priv_hands_over_shares() {
+        let mut game = tests::game(4);
+        game.sell_priv(0, PrivComId::BAndO, 100);
+        assert!(game.players[0]
+            .shares()
+            .contains_presidency(PubComId::BAndO));
+        assert_eq!(game.players[0].shares().count(PubComId::BAndO), 2);
+        assert!(!game.ipo.contains_presidency(PubComId::BAndO));
+        game.sell_priv(1, PrivComId::CV, 200);
+        assert_eq!(game.players[1].shares().count(PubComId::SRR), 1);
+        assert_eq!(game.players[1].capital(), 400);
+        assert_eq!(game.bank.capital(), 8000 - 2400 + 300);
+    }
+
+    #[test]
+    fn hexes_are_blocked_while_their_private_is_owned() {
+        let mut game = tests::game(4);
+        assert!(!game.hex_blocked(game.map.hex(3, 0).unwrap()));
+        game.sell_priv(0, PrivComId::CAndO, 100);
+        assert!(game.hex_blocked(game.map.hex(3, 0).unwrap()));
+        assert!(!game.hex_blocked(game.map.hex(5, 1).unwrap()));
+        game.close_priv(PrivComId::CAndO);
+        assert!(!game.hex_blocked(game.map.hex(3, 0).unwrap()));
+    }
+
+    #[test]
+    fn buy_priv() {
+        let mut game = tests::game(4);
+        game.sell_priv(0, PrivComId::CToP, 40);
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.enter_operating_round(0);
+        game.phase = PhaseId::Phase3;
+        assert_eq!(game.priv_price_range(PrivComId::CToP), (20, 80));
+        assert_eq!(game.buy_priv(PrivComId::CToP, 80), Ok(()));
+        assert_eq!(game.players[0].capital(), 650);
+        assert!(game.players[0].priv_coms().is_empty());
+        let prr = &game.pub_coms[&PubComId::PRR];
+        assert_eq!(prr.capital(), 720);
+        assert!(prr.priv_coms().contains(&PrivComId::CToP));
+        assert!(game.capital_conserved());
+        game.operate_priv_coms();
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 730);
+        assert!(game.capital_conserved());
+    }
+
+    #[test]
+    fn buy_priv_rejections() {
+        let mut game = tests::stock_round_game(4, false);
+        game.sell_priv(0, PrivComId::BAndO, 100);
+        game.sell_priv(0, PrivComId::DAndR(30), 30);
+        assert_eq!(
+            game.check_buy_priv(PrivComId::CToP, 40),
+            Err(ActionError::WrongRound)
+        );
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.enter_operating_round(0);
+        assert_eq!(
+            game.check_buy_priv(PrivComId::DAndR(20), 30),
+            Err(ActionError::WrongPhase)
+        );
+        game.phase = PhaseId::Phase3;
+        assert_eq!(
+            game.check_buy_priv(PrivComId::BAndO, 100),
+            Err(ActionError::PrivateUnavailable)
+        );
+        assert_eq!(
+            game.check_buy_priv(PrivComId::CToP, 40),
+            Err(ActionError::PrivateUnavailable)
+        );
+        assert_eq!(game.priv_price_range(PrivComId::DAndR(20)), (15, 60));
+        assert_eq!(
+            game.check_buy_priv(PrivComId::DAndR(20), 14),
+            Err(ActionError::InvalidPrice)
+        );
+        assert_eq!(
+            game.check_buy_priv(PrivComId::DAndR(20), 61),
+            Err(ActionError::InvalidPrice)
+        );
+        game.pub_coms
+            .get_mut(&PubComId::PRR)
+            .unwrap()
+            .remove_capital(790);
+        assert_eq!(
+            game.check_buy_priv(PrivComId::DAndR(20), 15),
+            Err(ActionError::InsufficientCapital)
+        );
+    }
+
+    #[test]
+    fn lay_priv_tile() {
+        let mut game = operating_round_game(PrivComId::CAndO);
+        assert_eq!(game.lay_priv_tile(PrivComId::CAndO, 3, 0, 57, 1), Ok(()));
+        assert_eq!(game.map.hex(3, 0).unwrap().tile_id(), Some(57));
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 815);
+        assert!(!owned(&game, PrivComId::CAndO));
+    }
+
+    #[test]
+    fn lay_priv_tile_rejections() {
+        let mut game = tests::stock_round_game(4, false);
+        assert_eq!(
+            game.check_lay_priv_tile(PrivComId::CAndO, 3, 0, 57, 1),
+            Err(ActionError::WrongRound)
+        );
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.enter_operating_round(0);
+        assert_eq!(
+            game.check_lay_priv_tile(PrivComId::CAndO, 3, 0, 57, 1),
+            Err(ActionError::PrivateNotOwned)
+        );
+        let game = operating_round_game(PrivComId::DAndR(20));
+        assert_eq!(
+            game.check_lay_priv_tile(PrivComId::DAndR(20), 10, 1, 57, 1),
+            Err(ActionError::NoAbility)
+        );
+        let game = operating_round_game(PrivComId::CAndO);
+        assert_eq!(
+            game.check_lay_priv_tile(PrivComId::CAndO, 5, 0, 57, 1),
+            Err(ActionError::HexNotReserved)
+        );
+        assert_eq!(
+            game.check_lay_priv_tile(PrivComId::CAndO, 3, 0, 57, 6),
+            Err(ActionError::InvalidRotation)
+        );
+        assert_eq!(
+            game.check_lay_priv_tile(PrivComId::CAndO, 3, 0, 57, 0),
+            Err(ActionError::TrackBlocked)
+        );
+        let game = operating_round_game(PrivComId::BAndS);
+        assert_eq!(
+            game.check_lay_priv_tile(PrivComId::BAndS, 5, 1, 7, 0),
+            Err(ActionError::NotConnected)
+        );
+    }
+
+    #[test]
+    fn privs_close_on_trains_and_phases() {
+        let mut game = tests::game(4);
+        game.sell_priv(0, PrivComId::BAndO, 100);
+        game.sell_priv(1, PrivComId::CV, 200);
+        game.sell_priv(2, PrivComId::CAndO, 100);
+        game.close_privs_on_train(PubComId::PRR);
+        assert!(owned(&game, PrivComId::BAndO) && owned(&game, PrivComId::CV));
+        game.close_privs_on_train(PubComId::BAndO);
+        assert!(!owned(&game, PrivComId::BAndO));
+        game.close_privs_on_train(PubComId::SRR);
+        assert!(!owned(&game, PrivComId::CV));
+        game.close_privs_on_phase(PhaseId::Phase4);
+        assert!(owned(&game, PrivComId::CAndO));
+        game.close_privs_on_phase(PhaseId::Phase5);
+        assert!(!owned(&game, PrivComId::CAndO));
+    }
+}
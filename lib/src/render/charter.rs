@@ -0,0 +1,111 @@
+use super::{escape, pub_com_colors};
+use crate::economy::{Player, PubCom};
+use crate::{Game, PrivComId, PubComId};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Returns an HTML fragment with a charter for each floated public company, showing its
+/// president, treasury, share value, trains, stations left and privates, followed by a charter
+/// for each player, showing their cash, shares and privates
+pub fn charters_html(game: &Game) -> String {
+    let mut s = String::new();
+    writeln!(s, r#"<div class="charters">"#).unwrap();
+    for pub_com in PubComId::values() {
+        if let Some(floated) = game.pub_com(pub_com) {
+            render_pub_com(&mut s, game, floated);
+        }
+    }
+    for player in game.players() {
+        render_player(&mut s, game, player);
+    }
+    writeln!(s, "</div>").unwrap();
+    s
+}
+
+fn render_pub_com(s: &mut String, game: &Game, pub_com: &PubCom) {
+    let (fill, text) = pub_com_colors(pub_com.id());
+    writeln!(s, r#"<div class="charter">"#).unwrap();
+    writeln!(
+        s,
+        r#"<h3 style="background:{};color:{}">{} &ndash; {}</h3>"#,
+        fill,
+        text,
+        escape(&pub_com.id().to_string()),
+        escape(pub_com.id().name())
+    )
+    .unwrap();
+    writeln!(s, "<table>").unwrap();
+    let president = game
+        .president(pub_com.id())
+        .map_or("-".to_string(), |p| format!("Player {}", p));
+    render_row(s, "President", &president);
+    render_row(s, "Treasury", &format!("${}", pub_com.capital()));
+    if let Some(value) = game.stock_chart().value(pub_com.id()) {
+        render_row(s, "Share value", &format!("${}", value));
+    }
+    if let Some(par) = game.par_track().value(pub_com.id()) {
+        render_row(s, "Par value", &format!("${}", par));
+    }
+    let trains: Vec<_> = pub_com.trains().iter().map(|t| t.to_string()).collect();
+    render_row(s, "Trains", &list(&trains));
+    render_row(s, "Stations left", &pub_com.stations_left().to_string());
+    render_row(s, "Privates", &priv_coms(pub_com.priv_coms()));
+    writeln!(s, "</table>").unwrap();
+    writeln!(s, "</div>").unwrap();
+}
+
+fn render_player(s: &mut String, game: &Game, player: &Player) {
+    writeln!(s, r#"<div class="charter">"#).unwrap();
+    writeln!(s, "<h3>Player {}</h3>", player.id()).unwrap();
+    writeln!(s, "<table>").unwrap();
+    render_row(s, "Cash", &format!("${}", player.capital()));
+    let shares: Vec<_> = PubComId::values()
+        .filter(|&p| player.shares().count(p) > 0)
+        .map(|p| {
+            let count = player.shares().count(p);
+            if player.shares().contains_presidency(p) {
+                format!("{} {}P", count, p)
+            } else {
+                format!("{} {}", count, p)
+            }
+        })
+        .collect();
+    render_row(s, "Shares", &list(&shares));
+    render_row(s, "Privates", &priv_coms(player.priv_coms()));
+    render_row(
+        s,
+        "Certificates",
+        &format!(
+            "{}/{}",
+            game.certificate_count(player),
+            game.certificate_limit()
+        ),
+    );
+    writeln!(s, "</table>").unwrap();
+    writeln!(s, "</div>").unwrap();
+}
+
+fn render_row(s: &mut String, label: &str, value: &str) {
+    writeln!(
+        s,
+        "<tr><th>{}</th><td>{}</td></tr>",
+        escape(label),
+        escape(value)
+    )
+    .unwrap();
+}
+
+fn priv_coms(priv_coms: &HashSet<PrivComId>) -> String {
+    let mut priv_coms: Vec<_> = priv_coms.iter().collect();
+    priv_coms.sort_by_key(|p| p.cost());
+    let priv_coms: Vec<_> = priv_coms.iter().map(|p| p.to_string()).collect();
+    list(&priv_coms)
+}
+
+fn list(items: &[String]) -> String {
+    if items.is_empty() {
+        "-".to_string()
+    } else {
+        items.join(" ")
+    }
+}
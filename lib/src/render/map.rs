@@ -0,0 +1,271 @@
+use super::{escape, render_token, tile_color};
+use crate::geography::{City, Hex, Location, Map, Stop};
+use crate::{PubComId, TerrainId};
+use std::fmt::Write;
+
+static RADIUS: f64 = 50.0;
+static CITY_RADIUS: f64 = 12.0;
+static PLAIN_COLOR: &str = "#d7e8c4";
+static TRACK_WIDTH: f64 = 8.0;
+
+/// Returns an SVG image of `map` with its laid tiles, track, cities, stations, terrain and
+/// private company reservations
+pub fn map_svg(map: &Map) -> String {
+    let height = RADIUS * 3f64.sqrt();
+    let svg_width = RADIUS * (1.5 * map.width() as f64 + 0.5);
+    let svg_height = height * (map.height() as f64 + 0.5);
+    let mut s = String::new();
+    writeln!(
+        s,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.0} {:.0}" font-family="sans-serif" text-anchor="middle">"#,
+        svg_width, svg_height, svg_width, svg_height
+    )
+    .unwrap();
+    for x in 0..map.width() {
+        for y in 0..map.height() {
+            if let Some(hex) = map.hex(x, y) {
+                let cx = RADIUS + 1.5 * RADIUS * x as f64;
+                let cy = height / 2.0 + height * y as f64 + (x % 2) as f64 * height / 2.0;
+                writeln!(s, r#"<g transform="translate({:.1},{:.1})">"#, cx, cy).unwrap();
+                render_hex(&mut s, hex);
+                writeln!(s, "</g>").unwrap();
+            }
+        }
+    }
+    writeln!(s, "</svg>").unwrap();
+    s
+}
+
+fn render_hex(s: &mut String, hex: &Hex) {
+    let fill = hex.tile().map_or(PLAIN_COLOR, |t| tile_color(t.color()));
+    let corners: Vec<String> = (0..6)
+        .map(|i| {
+            let (dx, dy) = polar(RADIUS, 60.0 * i as f64);
+            format!("{:.1},{:.1}", dx, dy)
+        })
+        .collect();
+    writeln!(
+        s,
+        r#"<polygon points="{}" fill="{}" stroke="black" stroke-width="1"/>"#,
+        corners.join(" "),
+        fill
+    )
+    .unwrap();
+    if hex.tile().is_none() {
+        render_terrain(s, hex.terrain());
+    }
+    let stop_count = hex.rails().iter().filter(|r| r.stop().is_some()).count();
+    let mut locations = Vec::new();
+    for rail in hex.rails() {
+        let stop = rail.stop().map(|_| stop_position(rail.edges(), stop_count));
+        render_track(s, rail.edges(), stop);
+        match (rail.stop(), stop) {
+            (Some(Stop::City(city)), Some(position)) => render_city(s, city, position),
+            (Some(Stop::Location(location)), _) => locations.push(location),
+            _ => (),
+        }
+    }
+    if hex.tile().is_none() {
+        let cities = hex.cities();
+        for (i, city) in cities.iter().enumerate() {
+            let offset = (i as f64 - (cities.len() - 1) as f64 / 2.0) * 3.0 * CITY_RADIUS;
+            render_city(s, city, (offset, 0.0));
+        }
+    }
+    if let Some(location) = locations.first() {
+        render_location(s, location, &locations);
+    }
+    let name = hex
+        .cities()
+        .iter()
+        .find_map(|c| c.name().map(|n| n.to_string()));
+    if let Some(name) = name {
+        writeln!(
+            s,
+            r#"<text y="{:.1}" font-size="9">{}</text>"#,
+            RADIUS * 0.75,
+            escape(&name)
+        )
+        .unwrap();
+    }
+    if let Some(id) = hex.tile_id() {
+        writeln!(
+            s,
+            r#"<text x="{:.1}" y="{:.1}" font-size="8">#{}</text>"#,
+            RADIUS * 0.55,
+            RADIUS * 0.7,
+            id
+        )
+        .unwrap();
+    }
+    if let Some(private) = hex.private() {
+        writeln!(
+            s,
+            r#"<rect x="-14" y="{:.1}" width="28" height="12" fill="white" stroke="black"/><text y="{:.1}" font-size="9">{}</text>"#,
+            -RADIUS * 0.75,
+            -RADIUS * 0.75 + 9.5,
+            escape(&private.to_string())
+        )
+        .unwrap();
+    }
+}
+
+fn render_terrain(s: &mut String, terrain: TerrainId) {
+    let (x, y) = (-RADIUS * 0.45, -RADIUS * 0.45);
+    match terrain {
+        TerrainId::Plain => return,
+        TerrainId::River => writeln!(
+            s,
+            r##"<path d="M{:.1},{:.1} q4,-4 8,0 t8,0 t8,0" fill="none" stroke="#1c75bc" stroke-width="2"/>"##,
+            x - 12.0,
+            y
+        )
+        .unwrap(),
+        TerrainId::Mountain => writeln!(
+            s,
+            r##"<path d="M{:.1},{:.1} l8,-12 l8,12 z" fill="#8b5a2b"/>"##,
+            x - 8.0,
+            y + 4.0
+        )
+        .unwrap(),
+    }
+    writeln!(
+        s,
+        r#"<text x="{:.1}" y="{:.1}" font-size="9">${}</text>"#,
+        x,
+        y + 14.0,
+        terrain.cost()
+    )
+    .unwrap();
+}
+
+fn render_track(s: &mut String, edges: &[u32], stop: Option<(f64, f64)>) {
+    let edge_point = |edge: u32| polar(RADIUS * 3f64.sqrt() / 2.0, 90.0 + 60.0 * edge as f64);
+    let path = match (stop, edges) {
+        (Some((sx, sy)), _) => edges
+            .iter()
+            .map(|&e| {
+                let (ex, ey) = edge_point(e);
+                format!("M{:.1},{:.1} L{:.1},{:.1}", ex, ey, sx, sy)
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+        (None, &[a, b]) => {
+            let ((ax, ay), (bx, by)) = (edge_point(a), edge_point(b));
+            format!("M{:.1},{:.1} Q0,0 {:.1},{:.1}", ax, ay, bx, by)
+        }
+        (None, _) => edges
+            .iter()
+            .map(|&e| {
+                let (ex, ey) = edge_point(e);
+                format!("M{:.1},{:.1} L0,0", ex, ey)
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+    if !path.is_empty() {
+        writeln!(
+            s,
+            r#"<path d="{}" fill="none" stroke="black" stroke-width="{}"/>"#,
+            path, TRACK_WIDTH
+        )
+        .unwrap();
+    }
+}
+
+fn render_city(s: &mut String, city: &City, (x, y): (f64, f64)) {
+    if city.spots() == 0 {
+        writeln!(
+            s,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="6" fill="black"/>"#,
+            x, y
+        )
+        .unwrap();
+        render_value(s, city.value(), (x + 10.0, y - 10.0));
+        return;
+    }
+    let mut stations: Vec<PubComId> = city.stations().iter().copied().collect();
+    stations.sort_by_key(|p| p.to_string());
+    let width = 2.0 * CITY_RADIUS * city.spots() as f64;
+    writeln!(
+        s,
+        r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="{:.1}" fill="white" stroke="black" stroke-width="2"/>"#,
+        x - width / 2.0,
+        y - CITY_RADIUS,
+        width,
+        2.0 * CITY_RADIUS,
+        CITY_RADIUS
+    )
+    .unwrap();
+    for spot in 0..city.spots() {
+        let spot_x = x - width / 2.0 + CITY_RADIUS * (2 * spot + 1) as f64;
+        writeln!(
+            s,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="white" stroke="black" stroke-width="2"/>"#,
+            spot_x, y, CITY_RADIUS
+        )
+        .unwrap();
+        if let Some(&pub_com) = stations.get(spot) {
+            render_token(s, pub_com, (spot_x, y), CITY_RADIUS - 2.0);
+        }
+    }
+    render_value(s, city.value(), (x + width / 2.0 + 6.0, y - CITY_RADIUS));
+}
+
+fn render_location(s: &mut String, location: &Location, locations: &[&Location]) {
+    let (a, b, c, d) = location.values();
+    for (i, value) in [a, b, c, d].iter().enumerate() {
+        let x = -30.0 + 15.0 * i as f64;
+        writeln!(
+            s,
+            r##"<rect x="{:.1}" y="-8" width="15" height="12" fill="{}" stroke="black"/><text x="{:.1}" y="1.5" font-size="8">{}</text>"##,
+            x,
+            ["#fde900", "#71bf44", "#cb7745", "#bcbdc0"][i],
+            x + 7.5,
+            value
+        )
+        .unwrap();
+    }
+    writeln!(
+        s,
+        r#"<text y="{:.1}" font-size="9" fill="white">{}</text>"#,
+        RADIUS * 0.45,
+        escape(location.name())
+    )
+    .unwrap();
+    if let Some(pub_com) = locations.iter().find_map(|l| l.station()) {
+        render_token(s, pub_com, (0.0, -RADIUS * 0.45), CITY_RADIUS - 2.0);
+    }
+}
+
+fn render_value(s: &mut String, value: u32, (x, y): (f64, f64)) {
+    writeln!(
+        s,
+        r#"<circle cx="{:.1}" cy="{:.1}" r="7" fill="white" stroke="black"/><text x="{:.1}" y="{:.1}" font-size="8">{}</text>"#,
+        x,
+        y,
+        x,
+        y + 3.0,
+        value
+    )
+    .unwrap();
+}
+
+/// Returns the position of the stop of a rail with `edges` in a hex with `stop_count` stops
+fn stop_position(edges: &[u32], stop_count: usize) -> (f64, f64) {
+    if stop_count <= 1 || edges.is_empty() {
+        return (0.0, 0.0);
+    }
+    let (mut x, mut y) = (0.0, 0.0);
+    for &edge in edges {
+        let (dx, dy) = polar(RADIUS * 0.45, 90.0 + 60.0 * edge as f64);
+        x += dx / edges.len() as f64;
+        y += dy / edges.len() as f64;
+    }
+    (x, y)
+}
+
+fn polar(radius: f64, degrees: f64) -> (f64, f64) {
+    let radians = degrees.to_radians();
+    (radius * radians.cos(), radius * radians.sin())
+}
@@ -0,0 +1,90 @@
+use super::render_token;
+use crate::economy::{ParTrack, StockChart};
+use crate::PubComId;
+use std::fmt::Write;
+
+static CELL_WIDTH: f64 = 50.0;
+static CELL_HEIGHT: f64 = 64.0;
+static TOKEN_RADIUS: f64 = 10.0;
+static STACK_OFFSET: f64 = 4.0;
+
+/// Returns an SVG image of `stock_chart` with the tokens of the public companies on each share
+/// value stacked so that the token operating first is on top
+pub fn stock_chart_svg(stock_chart: &StockChart) -> String {
+    let values = stock_chart.values();
+    let columns = values.iter().map(|row| row.len()).max().unwrap_or(0);
+    let svg_width = CELL_WIDTH * columns as f64;
+    let svg_height = CELL_HEIGHT * values.len() as f64;
+    let mut s = String::new();
+    writeln!(
+        s,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.0} {:.0}" font-family="sans-serif" text-anchor="middle">"#,
+        svg_width, svg_height, svg_width, svg_height
+    )
+    .unwrap();
+    for (row, row_values) in values.iter().enumerate() {
+        for (column, value) in row_values.iter().enumerate() {
+            let (x, y) = (CELL_WIDTH * column as f64, CELL_HEIGHT * row as f64);
+            render_cell(&mut s, *value, (x, y));
+            let mut tokens: Vec<_> = PubComId::values()
+                .filter_map(|p| match stock_chart.position(p) {
+                    Some((r, c, z)) if r == row && c == column => Some((z, p)),
+                    _ => None,
+                })
+                .collect();
+            tokens.sort_by_key(|&(z, _)| z);
+            for &(z, pub_com) in tokens.iter().rev() {
+                let center = (
+                    x + CELL_WIDTH / 2.0,
+                    y + 16.0 + TOKEN_RADIUS + STACK_OFFSET * z as f64,
+                );
+                render_token(&mut s, pub_com, center, TOKEN_RADIUS);
+            }
+        }
+    }
+    writeln!(s, "</svg>").unwrap();
+    s
+}
+
+/// Returns an SVG image of `par_track` with the tokens of the public companies on their par values
+pub fn par_track_svg(par_track: &ParTrack) -> String {
+    let svg_width = CELL_WIDTH * par_track.values().len() as f64;
+    let mut s = String::new();
+    writeln!(
+        s,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.0} {:.0}" font-family="sans-serif" text-anchor="middle">"#,
+        svg_width, CELL_HEIGHT, svg_width, CELL_HEIGHT
+    )
+    .unwrap();
+    for (i, &par) in par_track.values().iter().enumerate() {
+        let x = CELL_WIDTH * i as f64;
+        render_cell(&mut s, par, (x, 0.0));
+        let tokens: Vec<_> = PubComId::values()
+            .filter(|&p| par_track.value(p) == Some(par))
+            .collect();
+        for (j, &pub_com) in tokens.iter().enumerate() {
+            let center = (
+                x + CELL_WIDTH / 2.0,
+                16.0 + TOKEN_RADIUS + STACK_OFFSET * j as f64,
+            );
+            render_token(&mut s, pub_com, center, TOKEN_RADIUS);
+        }
+    }
+    writeln!(s, "</svg>").unwrap();
+    s
+}
+
+fn render_cell(s: &mut String, value: u32, (x, y): (f64, f64)) {
+    writeln!(
+        s,
+        r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="white" stroke="black"/><text x="{:.1}" y="{:.1}" font-size="11">{}</text>"#,
+        x,
+        y,
+        CELL_WIDTH,
+        CELL_HEIGHT,
+        x + CELL_WIDTH / 2.0,
+        y + 12.0,
+        value
+    )
+    .unwrap();
+}
@@ -0,0 +1,64 @@
+//! Game rendering logic
+
+mod charter;
+mod map;
+mod market;
+
+pub use charter::charters_html;
+pub use map::map_svg;
+pub use market::{par_track_svg, stock_chart_svg};
+
+use crate::{ColorId, PubComId};
+use std::fmt::Write;
+
+/// Returns the fill and text colors of the tokens of `pub_com`
+fn pub_com_colors(pub_com: PubComId) -> (&'static str, &'static str) {
+    match pub_com {
+        PubComId::BAndO => ("#0189d1", "white"),
+        PubComId::CAndA => ("#f58220", "black"),
+        PubComId::CAndO => ("#a2dced", "black"),
+        PubComId::LV => ("#ffe600", "black"),
+        PubComId::NAndW => ("#7b352a", "white"),
+        PubComId::PRR => ("#237333", "white"),
+        PubComId::PAndLE => ("#000000", "white"),
+        PubComId::SRR => ("#d81e3e", "white"),
+    }
+}
+
+/// Returns the fill color of tiles of `color`
+fn tile_color(color: ColorId) -> &'static str {
+    match color {
+        ColorId::Yellow => "#fde900",
+        ColorId::Green => "#71bf44",
+        ColorId::Brown => "#cb7745",
+        ColorId::Gray => "#bcbdc0",
+        ColorId::Red => "#ec232a",
+    }
+}
+
+/// Returns `s` with the characters special to XML escaped
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Writes a token of `pub_com` with `radius` centered at `x`, `y` to `s`
+fn render_token(s: &mut String, pub_com: PubComId, (x, y): (f64, f64), radius: f64) {
+    let (fill, text) = pub_com_colors(pub_com);
+    writeln!(
+        s,
+        r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}" stroke="black"/><text x="{:.1}" y="{:.1}" font-size="{:.1}" fill="{}">{}</text>"#,
+        x,
+        y,
+        radius,
+        fill,
+        x,
+        y + radius / 4.0,
+        radius * 0.7,
+        text,
+        escape(&pub_com.to_string())
+    )
+    .unwrap();
+}
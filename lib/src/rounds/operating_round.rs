@@ -1,13 +1,19 @@
-use crate::OperatingPhaseId;
-use crate::PubComId;
+use crate::economy::PubCom;
+use crate::geography::{RouteSet, TrackLayMap};
+use crate::{ActionError, EndReason, Game, OperatingPhaseId, PhaseId, PubComId, RoundId, TrainId};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
+static STATION_COST: u32 = 40;
+
 /// Represents an operating round
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OperatingRound {
     phase: OperatingPhaseId,
     operating_rounds_left: u32,
+    #[serde(serialize_with = "crate::sorted_serde::set")]
     pub_coms_to_operate: HashSet<PubComId>,
+    current: Option<PubComId>,
 }
 
 impl OperatingRound {
@@ -16,11 +22,994 @@ impl OperatingRound {
             phase: OperatingPhaseId::LayTrack,
             operating_rounds_left,
             pub_coms_to_operate,
+            current: None,
         }
     }
 
-    /// Returns the public companies yet to operate in this `OperatingRound
+    pub(crate) fn start(&mut self, pub_com: PubComId) {
+        self.pub_coms_to_operate.remove(&pub_com);
+        self.current = Some(pub_com);
+        self.phase = OperatingPhaseId::LayTrack;
+    }
+
+    pub(crate) fn set_phase(&mut self, phase: OperatingPhaseId) {
+        self.phase = phase;
+    }
+
+    /// Returns the current phase of this `OperatingRound`
+    pub fn phase(&self) -> OperatingPhaseId {
+        self.phase
+    }
+
+    /// Returns the number of operating rounds left after this `OperatingRound`
+    pub fn operating_rounds_left(&self) -> u32 {
+        self.operating_rounds_left
+    }
+
+    /// Returns the public companies yet to operate in this `OperatingRound`
     pub fn pub_coms_to_operate(&self) -> &HashSet<PubComId> {
         &self.pub_coms_to_operate
     }
+
+    /// Returns the public company currently operating in this `OperatingRound`
+    pub fn current(&self) -> Option<PubComId> {
+        self.current
+    }
+}
+
+impl Game {
+    /// Returns the public company currently operating, if any
+    pub fn operating_pub_com(&self) -> Option<PubComId> {
+        if let RoundId::OperatingRound(operating_round) = &self.round {
+            operating_round.current()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the legal track lays of the operating public company, if any
+    pub fn track_lay_map(&self) -> Option<TrackLayMap> {
+        let pub_com = self
+            .check_operating_phase(OperatingPhaseId::LayTrack)
+            .ok()?
+            .id();
+        let mut track_lay_map = self.map.track_lay_map(pub_com, &self.tile_set, self.phase);
+        track_lay_map.retain(|x, y, tile, rotation| self.lay_tile_allowed(x, y, tile, rotation));
+        Some(track_lay_map)
+    }
+
+    /// Returns whether laying tile `tile` rotated `rotation` steps clockwise at `x`, `y` is
+    /// allowed
+    pub fn lay_tile_allowed(&self, x: usize, y: usize, tile: i32, rotation: u32) -> bool {
+        self.check_lay_tile(x, y, tile, rotation).is_ok()
+    }
+
+    /// Returns `Ok` if laying tile `tile` rotated `rotation` steps clockwise at `x`, `y` is
+    /// allowed, otherwise the reason it is not
+    pub fn check_lay_tile(
+        &self,
+        x: usize,
+        y: usize,
+        tile: i32,
+        rotation: u32,
+    ) -> Result<(), ActionError> {
+        let pub_com = self.check_operating_phase(OperatingPhaseId::LayTrack)?;
+        let hex = self.map.hex(x, y).ok_or(ActionError::InvalidHex)?;
+        let (new_tile, count) = self
+            .tile_set
+            .tile(tile)
+            .ok_or(ActionError::TileUnavailable)?;
+        if rotation > 5 {
+            Err(ActionError::InvalidRotation)
+        } else if count == 0 {
+            Err(ActionError::TileUnavailable)
+        } else if !self.phase.allows_color(new_tile.color()) {
+            Err(ActionError::ColorUnavailable)
+        } else if self.hex_blocked(hex) {
+            Err(ActionError::HexBlocked)
+        } else if hex.tile().is_none() && pub_com.capital() < hex.terrain().cost() {
+            Err(ActionError::InsufficientCapital)
+        } else {
+            let new_tile = new_tile.rotated(rotation);
+            hex.check_lay_tile(tile, &new_tile)?;
+            let reach = self.map.reachable_edges(pub_com.id());
+            self.map.check_track(&reach, pub_com.id(), x, y, &new_tile)
+        }
+    }
+
+    /// Lays tile `tile` rotated `rotation` steps clockwise at `x`, `y`, paying the terrain cost of
+    /// the hex if no tile has been laid there before and returning any replaced tile to the tile set
+    pub fn lay_tile(
+        &mut self,
+        x: usize,
+        y: usize,
+        tile: i32,
+        rotation: u32,
+    ) -> Result<(), ActionError> {
+        self.check_lay_tile(x, y, tile, rotation)?;
+        let hex = self.map.hex(x, y).unwrap();
+        if hex.tile().is_none() {
+            let cost = hex.terrain().cost();
+            let pub_com = self.operating_pub_com().unwrap();
+            self.pub_coms
+                .get_mut(&pub_com)
+                .unwrap()
+                .remove_capital(cost);
+            self.bank.deposit(cost);
+        }
+        if let Some(old_tile) = hex.tile_id() {
+            self.tile_set.return_tile(old_tile);
+        }
+        let new_tile = self.tile_set.take_tile(tile).rotated(rotation);
+        self.map.lay_tile(x, y, tile, new_tile);
+        self.advance_operating_phase();
+        Ok(())
+    }
+
+    /// Returns whether placing a station in city `city` at `x`, `y` is allowed
+    pub fn place_station_allowed(&self, x: usize, y: usize, city: usize) -> bool {
+        self.check_place_station(x, y, city).is_ok()
+    }
+
+    /// Returns `Ok` if placing a station in city `city` at `x`, `y` is allowed, otherwise the
+    /// reason it is not
+    pub fn check_place_station(&self, x: usize, y: usize, city: usize) -> Result<(), ActionError> {
+        let pub_com = self.check_operating_phase(OperatingPhaseId::PlaceStation)?;
+        let hex = self.map.hex(x, y).ok_or(ActionError::InvalidHex)?;
+        let index = city;
+        let city = hex.city(index).ok_or(ActionError::InvalidCity)?;
+        let reachable = self
+            .map
+            .reachable_edges(pub_com.id())
+            .get(&(x, y))
+            .is_some_and(|edges| hex.city_edges(index).iter().any(|e| edges.contains(e)));
+        if pub_com.stations_left() == 0 {
+            Err(ActionError::NoStationsLeft)
+        } else if city.stations().contains(&pub_com.id()) {
+            Err(ActionError::AlreadyStationed)
+        } else if city.stations().len() >= city.spots() {
+            Err(ActionError::CityFull)
+        } else if !reachable {
+            Err(ActionError::CityNotReachable)
+        } else if city.stations().len() + self.reserved_spots(pub_com.id(), x, y, index)
+            >= city.spots()
+        {
+            Err(ActionError::CityReserved)
+        } else if pub_com.capital() < STATION_COST {
+            Err(ActionError::InsufficientCapital)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Places a station in city `city` at `x`, `y`
+    pub fn place_station(&mut self, x: usize, y: usize, city: usize) -> Result<(), ActionError> {
+        self.check_place_station(x, y, city)?;
+        let pub_com = self.operating_pub_com().unwrap();
+        self.map.place_station(pub_com, x, y, city);
+        let pub_com = self.pub_coms.get_mut(&pub_com).unwrap();
+        pub_com.use_station();
+        pub_com.remove_capital(STATION_COST);
+        self.bank.deposit(STATION_COST);
+        self.advance_operating_phase();
+        Ok(())
+    }
+
+    /// Returns the number of spots in city `city` at `x`, `y` reserved for the home stations of
+    /// public companies other than `pub_com` that have not floated
+    fn reserved_spots(&self, pub_com: PubComId, x: usize, y: usize, city: usize) -> usize {
+        PubComId::values()
+            .filter(|&p| p != pub_com && !self.pub_coms.contains_key(&p))
+            .filter(|&p| self.map.home_city(p) == Some((x, y, city)))
+            .count()
+    }
+
+    /// Returns the revenue-maximising routes of the trains of the operating public company, if any
+    pub fn routes(&self) -> Option<RouteSet> {
+        let pub_com = &self.pub_coms[&self.operating_pub_com()?];
+        Some(
+            self.map
+                .best_routes(pub_com.id(), pub_com.trains(), self.phase),
+        )
+    }
+
+    /// Returns whether running trains is allowed
+    pub fn run_trains_allowed(&self) -> bool {
+        self.check_run_trains().is_ok()
+    }
+
+    /// Returns `Ok` if running trains is allowed, otherwise the reason it is not
+    pub fn check_run_trains(&self) -> Result<(), ActionError> {
+        self.check_operating_phase(OperatingPhaseId::DistributeEarnings)?;
+        Ok(())
+    }
+
+    /// Runs trains along their revenue-maximising routes, paying the revenue out to the
+    /// shareholders if `payout` and otherwise withholding it in the treasury
+    pub fn run_trains(&mut self, payout: bool) -> Result<(), ActionError> {
+        self.check_run_trains()?;
+        let revenue = self.routes().unwrap().revenue();
+        self.distribute_earnings(revenue, payout);
+        self.advance_operating_phase();
+        Ok(())
+    }
+
+    /// Returns whether buying the next train from the depot is allowed
+    pub fn buy_train_allowed(&self) -> bool {
+        self.check_buy_train().is_ok()
+    }
+
+    /// Returns `Ok` if buying the next train from the depot is allowed, otherwise the reason it is
+    /// not
+    pub fn check_buy_train(&self) -> Result<(), ActionError> {
+        let pub_com = self.check_operating_phase(OperatingPhaseId::BuyTrains)?;
+        let train = self
+            .train_set
+            .next_train()
+            .ok_or(ActionError::NoTrainsAvailable)?;
+        self.check_train_limit(pub_com)?;
+        self.check_train_cost(pub_com, train.cost())
+    }
+
+    /// Buys the next train from the depot, with the president contributing the missing capital if
+    /// the public company owns no trains, and advances the phase on the first train of a new type
+    pub fn buy_train(&mut self) -> Result<(), ActionError> {
+        self.check_buy_train()?;
+        let train = self.train_set.next_train().unwrap();
+        self.train_set.remove_train(train);
+        self.pay_for_train(train.cost());
+        let pub_com = self.operating_pub_com().unwrap();
+        self.pub_coms.get_mut(&pub_com).unwrap().add_train(train);
+        self.close_privs_on_train(pub_com);
+        if train.phase() > self.phase {
+            self.enter_phase(train.phase());
+        }
+        Ok(())
+    }
+
+    /// Returns whether buying `train` from the open market is allowed
+    pub fn buy_market_train_allowed(&self, train: TrainId) -> bool {
+        self.check_buy_market_train(train).is_ok()
+    }
+
+    /// Returns `Ok` if buying `train` from the open market is allowed, otherwise the reason it is
+    /// not
+    pub fn check_buy_market_train(&self, train: TrainId) -> Result<(), ActionError> {
+        let pub_com = self.check_operating_phase(OperatingPhaseId::BuyTrains)?;
+        if !self.train_set.market().contains(&train) {
+            Err(ActionError::TrainUnavailable)
+        } else {
+            self.check_train_limit(pub_com)?;
+            self.check_train_cost(pub_com, train.cost())
+        }
+    }
+
+    /// Buys `train` from the open market, with the president contributing the missing capital if
+    /// the public company owns no trains
+    pub fn buy_market_train(&mut self, train: TrainId) -> Result<(), ActionError> {
+        self.check_buy_market_train(train)?;
+        self.train_set.remove_market_train(train);
+        self.pay_for_train(train.cost());
+        let pub_com = self.operating_pub_com().unwrap();
+        self.pub_coms.get_mut(&pub_com).unwrap().add_train(train);
+        self.close_privs_on_train(pub_com);
+        Ok(())
+    }
+
+    /// Returns whether buying `train` from `seller` for `price` is allowed
+    pub fn buy_pub_com_train_allowed(&self, seller: PubComId, train: TrainId, price: u32) -> bool {
+        self.check_buy_pub_com_train(seller, train, price).is_ok()
+    }
+
+    /// Returns `Ok` if buying `train` from `seller` for `price` is allowed, otherwise the reason
+    /// it is not
+    pub fn check_buy_pub_com_train(
+        &self,
+        seller: PubComId,
+        train: TrainId,
+        price: u32,
+    ) -> Result<(), ActionError> {
+        let pub_com = self.check_operating_phase(OperatingPhaseId::BuyTrains)?;
+        let seller_pub_com = self
+            .pub_coms
+            .get(&seller)
+            .filter(|_| seller != pub_com.id())
+            .ok_or(ActionError::TrainUnavailable)?;
+        if !seller_pub_com.trains().contains(&train) {
+            Err(ActionError::TrainUnavailable)
+        } else if self.president(seller) != self.president(pub_com.id()) {
+            Err(ActionError::NotSamePresident)
+        } else if price == 0 {
+            Err(ActionError::InvalidPrice)
+        } else if pub_com.capital() < price {
+            Err(ActionError::InsufficientCapital)
+        } else {
+            self.check_train_limit(pub_com)
+        }
+    }
+
+    /// Buys `train` from `seller` for `price`
+    pub fn buy_pub_com_train(
+        &mut self,
+        seller: PubComId,
+        train: TrainId,
+        price: u32,
+    ) -> Result<(), ActionError> {
+        self.check_buy_pub_com_train(seller, train, price)?;
+        let seller = self.pub_coms.get_mut(&seller).unwrap();
+        seller.remove_train(train);
+        seller.add_capital(price);
+        let pub_com = self.operating_pub_com().unwrap();
+        let buyer = self.pub_coms.get_mut(&pub_com).unwrap();
+        buyer.remove_capital(price);
+        buyer.add_train(train);
+        self.close_privs_on_train(pub_com);
+        Ok(())
+    }
+
+    /// Returns whether declaring bankruptcy is allowed
+    pub fn declare_bankruptcy_allowed(&self) -> bool {
+        self.check_declare_bankruptcy().is_ok()
+    }
+
+    /// Returns `Ok` if declaring bankruptcy is allowed, otherwise the reason it is not
+    pub fn check_declare_bankruptcy(&self) -> Result<(), ActionError> {
+        let pub_com = self.check_operating_phase(OperatingPhaseId::BuyTrains)?;
+        let can_buy_pub_com_train = PubComId::values()
+            .filter_map(|p| self.pub_coms.get(&p))
+            .any(|seller| {
+                seller
+                    .trains()
+                    .iter()
+                    .any(|&t| self.buy_pub_com_train_allowed(seller.id(), t, 1))
+            });
+        if !pub_com.trains().is_empty()
+            || self.buy_train_allowed()
+            || self
+                .train_set
+                .market()
+                .iter()
+                .any(|&t| self.buy_market_train_allowed(t))
+            || can_buy_pub_com_train
+        {
+            Err(ActionError::NotBankrupt)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Declares the president of the operating public company bankrupt since it owns no trains
+    /// and no train can be funded, ending the game
+    pub fn declare_bankruptcy(&mut self) -> Result<(), ActionError> {
+        self.check_declare_bankruptcy()?;
+        let pub_com = self.operating_pub_com().unwrap();
+        let player = self.president(pub_com).unwrap();
+        self.end_game(EndReason::Bankruptcy { pub_com, player });
+        Ok(())
+    }
+
+    pub(crate) fn check_operating_phase(
+        &self,
+        phase: OperatingPhaseId,
+    ) -> Result<&PubCom, ActionError> {
+        if let RoundId::OperatingRound(operating_round) = &self.round {
+            if operating_round.phase() == phase {
+                Ok(&self.pub_coms[&operating_round.current().unwrap()])
+            } else {
+                Err(ActionError::WrongOperatingPhase)
+            }
+        } else {
+            Err(ActionError::WrongRound)
+        }
+    }
+
+    pub(crate) fn enter_phase(&mut self, phase: PhaseId) {
+        self.phase = phase;
+        self.close_privs_on_phase(phase);
+        if let Some(rusted) = phase.rusted_train() {
+            self.train_set.rust(rusted);
+            for pub_com in self.pub_coms.values_mut() {
+                pub_com.rust(rusted);
+            }
+        }
+        for pub_com in self.pub_coms.values_mut() {
+            while pub_com.trains().len() > phase.train_limit() {
+                let train = *pub_com.trains().iter().min().unwrap();
+                pub_com.remove_train(train);
+                self.train_set.add_market_train(train);
+            }
+        }
+    }
+
+    pub(crate) fn advance_operating_phase(&mut self) {
+        if let RoundId::OperatingRound(operating_round) = &mut self.round {
+            match operating_round.phase() {
+                OperatingPhaseId::LayTrack => {
+                    operating_round.set_phase(OperatingPhaseId::PlaceStation);
+                }
+                OperatingPhaseId::PlaceStation => {
+                    operating_round.set_phase(OperatingPhaseId::DistributeEarnings);
+                }
+                OperatingPhaseId::DistributeEarnings => {
+                    operating_round.set_phase(OperatingPhaseId::BuyTrains);
+                }
+                OperatingPhaseId::BuyTrains => {
+                    let pub_com = operating_round.current().unwrap();
+                    self.pub_coms.get_mut(&pub_com).unwrap().set_operated();
+                    self.start_next_pub_com();
+                }
+            }
+        } else {
+            unreachable!();
+        }
+    }
+
+    /// Pays `revenue` out per share if `payout`, with bank pool shares paying the public company,
+    /// and moves the share value right, otherwise withholds it and moves the share value left
+    pub(crate) fn distribute_earnings(&mut self, revenue: u32, payout: bool) {
+        let pub_com = self.operating_pub_com().unwrap();
+        if payout && revenue > 0 {
+            let dividend = revenue / 10;
+            for player in &mut self.players {
+                let count = player.shares().count(pub_com);
+                self.bank.withdraw(dividend * count);
+                player.add_capital(dividend * count);
+            }
+            let pool_count = self.pool.count(pub_com);
+            self.bank.withdraw(dividend * pool_count);
+            self.pub_coms
+                .get_mut(&pub_com)
+                .unwrap()
+                .add_capital(dividend * pool_count);
+            self.stock_chart.move_right(pub_com);
+        } else {
+            self.bank.withdraw(revenue);
+            self.pub_coms
+                .get_mut(&pub_com)
+                .unwrap()
+                .add_capital(revenue);
+            self.stock_chart.move_left(pub_com);
+        }
+    }
+
+    fn check_train_limit(&self, pub_com: &PubCom) -> Result<(), ActionError> {
+        if pub_com.trains().len() >= self.phase.train_limit() {
+            Err(ActionError::TrainLimit)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_train_cost(&self, pub_com: &PubCom, cost: u32) -> Result<(), ActionError> {
+        if pub_com.capital() >= cost {
+            return Ok(());
+        }
+        let cheapest = self
+            .train_set
+            .next_train()
+            .into_iter()
+            .chain(self.train_set.market().iter().copied())
+            .map(|t| t.cost())
+            .min();
+        let president = &self.players[self.president(pub_com.id()).unwrap()];
+        if pub_com.trains().is_empty()
+            && Some(cost) == cheapest
+            && pub_com.capital() + president.capital() >= cost
+        {
+            Ok(())
+        } else {
+            Err(ActionError::InsufficientCapital)
+        }
+    }
+
+    fn pay_for_train(&mut self, cost: u32) {
+        let pub_com = self.operating_pub_com().unwrap();
+        let president = self.president(pub_com).unwrap();
+        let pub_com = self.pub_coms.get_mut(&pub_com).unwrap();
+        let capital = pub_com.capital().min(cost);
+        pub_com.remove_capital(capital);
+        self.players[president].remove_capital(cost - capital);
+        self.bank.deposit(cost);
+    }
+
+    pub(crate) fn start_next_pub_com(&mut self) {
+        if let RoundId::OperatingRound(operating_round) = &mut self.round {
+            let operating_rounds_left = operating_round.operating_rounds_left();
+            if let Some(pub_com) = self
+                .stock_chart
+                .next_to_operate(operating_round.pub_coms_to_operate())
+            {
+                operating_round.start(pub_com);
+                self.current_player = self.president(pub_com).unwrap();
+            } else if operating_rounds_left > 0 {
+                self.enter_operating_round(operating_rounds_left - 1);
+            } else if self.bank.is_broken() {
+                self.end_game(EndReason::BankBroken);
+            } else {
+                self.enter_stock_round();
+            }
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::tests;
+    use crate::{
+        Action, ActionError, Game, GameConfig, OperatingPhaseId, PhaseId, PubComId, RoundId,
+        TrainId,
+    };
+
+    fn operating_round_game() -> Game {
+        let mut game = tests::game(4);
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.enter_operating_round(0);
+        game
+    }
+
+    fn phase(game: &Game) -> OperatingPhaseId {
+        if let RoundId::OperatingRound(operating_round) = &game.round {
+            operating_round.phase()
+        } else {
+            panic!("not in an operating round");
+        }
+    }
+
+    fn set_phase(game: &mut Game, phase: OperatingPhaseId) {
+        if let RoundId::OperatingRound(operating_round) = &mut game.round {
+            operating_round.set_phase(phase);
+        }
+    }
+
+    /// Lays a tile in Hagerstown, so that track laid in Harrisburg reaches its city
+    fn lay_hagerstown(game: &mut Game) {
+        let tile = game.tile_set.take_tile(57).rotated(1);
+        game.map.lay_tile(4, 1, 57, tile);
+    }
+
+    #[test]
+    fn full_operating_round() {
+        let mut game = operating_round_game();
+        lay_hagerstown(&mut game);
+        assert_eq!(game.operating_pub_com(), Some(PubComId::PRR));
+        assert_eq!(game.current_player(), 1);
+        assert_eq!(game.lay_tile(5, 0, 57, 1), Ok(()));
+        assert_eq!(phase(&game), OperatingPhaseId::PlaceStation);
+        assert_eq!(game.place_station(4, 1, 0), Ok(()));
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 760);
+        assert_eq!(game.pub_coms[&PubComId::PRR].stations_left(), 2);
+        assert_eq!(phase(&game), OperatingPhaseId::DistributeEarnings);
+        assert_eq!(game.run_trains(false), Ok(()));
+        assert_eq!(phase(&game), OperatingPhaseId::BuyTrains);
+        assert_eq!(game.buy_train(), Ok(()));
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 680);
+        assert_eq!(game.pub_coms[&PubComId::PRR].trains(), &[TrainId::Range2]);
+        assert_eq!(game.pass(), Ok(()));
+        assert!(game.pub_coms[&PubComId::PRR].operated());
+        assert!(matches!(&game.round, RoundId::StockRound(s) if s.sell_allowed()));
+    }
+
+    #[test]
+    fn capital_is_conserved_through_an_operating_round() {
+        let mut game = operating_round_game();
+        lay_hagerstown(&mut game);
+        assert!(game.capital_conserved());
+        let actions = [
+            Action::LayTile {
+                x: 5,
+                y: 0,
+                tile: 57,
+                rotation: 1,
+            },
+            Action::PlaceStation {
+                x: 4,
+                y: 1,
+                city: 0,
+            },
+            Action::RunTrains { payout: false },
+            Action::BuyTrain,
+            Action::Pass,
+        ];
+        for &action in &actions {
+            assert_eq!(game.apply(action), Ok(()));
+            assert!(game.capital_conserved());
+        }
+        assert_eq!(game.bank().capital(), 8000 - 2400 - 800 + 40 + 80);
+    }
+
+    #[test]
+    fn run_trains_pays_out_or_withholds() {
+        for &payout in &[true, false] {
+            let mut game = operating_round_game();
+            game.lay_tile(5, 0, 57, 1).unwrap();
+            lay_hagerstown(&mut game);
+            game.pass().unwrap();
+            game.pub_coms
+                .get_mut(&PubComId::PRR)
+                .unwrap()
+                .add_train(TrainId::Range2);
+            assert_eq!(game.check_pass(), Err(ActionError::MustRunTrains));
+            assert_eq!(game.routes().unwrap().revenue(), 40);
+            assert_eq!(game.run_trains(payout), Ok(()));
+            if payout {
+                assert_eq!(game.players[1].capital(), 620);
+                assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 800);
+                assert_eq!(game.stock_chart.value(PubComId::PRR), Some(85));
+            } else {
+                assert_eq!(game.players[1].capital(), 600);
+                assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 840);
+                assert_eq!(game.stock_chart.value(PubComId::PRR), Some(75));
+            }
+        }
+    }
+
+    #[test]
+    fn pool_shares_pay_the_pub_com() {
+        let mut game = operating_round_game();
+        game.lay_tile(5, 0, 57, 1).unwrap();
+        let tile = game.tile_set.take_tile(57).rotated(1);
+        game.map.lay_tile(4, 1, 57, tile);
+        game.pass().unwrap();
+        game.pub_coms
+            .get_mut(&PubComId::PRR)
+            .unwrap()
+            .add_train(TrainId::Range2);
+        game.players[1].shares_mut().remove_shares(PubComId::PRR, 2);
+        game.pool.add_shares(PubComId::PRR, 2);
+        assert_eq!(game.run_trains(true), Ok(()));
+        assert_eq!(game.players[1].capital(), 612);
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 808);
+    }
+
+    #[test]
+    fn passing_without_trains_withholds_nothing() {
+        let mut game = operating_round_game();
+        game.pass().unwrap();
+        game.pass().unwrap();
+        assert_eq!(phase(&game), OperatingPhaseId::DistributeEarnings);
+        assert_eq!(game.pass(), Ok(()));
+        assert_eq!(phase(&game), OperatingPhaseId::BuyTrains);
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 800);
+        assert_eq!(game.stock_chart.value(PubComId::PRR), Some(75));
+    }
+
+    #[test]
+    fn operating_actions_outside_operating_round_are_rejected() {
+        let game = tests::stock_round_game(4, true);
+        assert_eq!(
+            game.check_lay_tile(5, 2, 7, 0),
+            Err(ActionError::WrongRound)
+        );
+        assert_eq!(
+            game.check_place_station(4, 1, 0),
+            Err(ActionError::WrongRound)
+        );
+        assert_eq!(game.check_run_trains(), Err(ActionError::WrongRound));
+        assert_eq!(game.check_buy_train(), Err(ActionError::WrongRound));
+    }
+
+    #[test]
+    fn operating_actions_in_wrong_phase_are_rejected() {
+        let game = operating_round_game();
+        assert_eq!(
+            game.check_place_station(4, 1, 0),
+            Err(ActionError::WrongOperatingPhase)
+        );
+        assert_eq!(
+            game.check_run_trains(),
+            Err(ActionError::WrongOperatingPhase)
+        );
+        assert_eq!(
+            game.check_buy_train(),
+            Err(ActionError::WrongOperatingPhase)
+        );
+    }
+
+    #[test]
+    fn lay_tile_rejections() {
+        let game = operating_round_game();
+        assert_eq!(
+            game.check_lay_tile(0, 0, 7, 0),
+            Err(ActionError::InvalidHex)
+        );
+        assert_eq!(
+            game.check_lay_tile(5, 2, 1000, 0),
+            Err(ActionError::TileUnavailable)
+        );
+        assert_eq!(
+            game.check_lay_tile(5, 2, 7, 6),
+            Err(ActionError::InvalidRotation)
+        );
+        assert_eq!(
+            game.check_lay_tile(5, 2, 14, 0),
+            Err(ActionError::ColorUnavailable)
+        );
+        assert_eq!(
+            game.check_lay_tile(7, 2, 7, 0),
+            Err(ActionError::InvalidUpgrade)
+        );
+    }
+
+    #[test]
+    fn lay_tile_carries_stations_over() {
+        let mut game = operating_round_game();
+        assert_eq!(game.lay_tile(5, 0, 57, 1), Ok(()));
+        assert_eq!(game.tile_set.tile(57).unwrap().1, 6);
+        let city = game.map.hex(5, 0).unwrap().city(0).unwrap();
+        assert!(city.stations().contains(&PubComId::PRR));
+        assert_eq!(game.map.stations(PubComId::PRR), &[(5, 0, 0)]);
+
+        game.phase = PhaseId::Phase3;
+        set_phase(&mut game, OperatingPhaseId::LayTrack);
+        assert_eq!(game.lay_tile(5, 0, 14, 1), Ok(()));
+        assert_eq!(game.tile_set.tile(57).unwrap().1, 7);
+        assert_eq!(game.map.hex(5, 0).unwrap().tile_id(), Some(14));
+        let city = game.map.hex(5, 0).unwrap().city(0).unwrap();
+        assert!(city.stations().contains(&PubComId::PRR));
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 800);
+    }
+
+    #[test]
+    fn lay_tile_pays_terrain_cost() {
+        let mut game = operating_round_game();
+        game.lay_tile(5, 0, 57, 1).unwrap();
+        set_phase(&mut game, OperatingPhaseId::LayTrack);
+        assert_eq!(game.lay_tile(6, 1, 4, 0), Ok(()));
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 760);
+    }
+
+    #[test]
+    fn lay_tile_track_rejections() {
+        let game = operating_round_game();
+        assert_eq!(
+            game.check_lay_tile(6, 1, 4, 0),
+            Err(ActionError::NotConnected)
+        );
+        assert_eq!(
+            game.check_lay_tile(5, 0, 57, 0),
+            Err(ActionError::TrackBlocked)
+        );
+    }
+
+    #[test]
+    fn lay_tile_upgrade_rejections() {
+        let mut game = operating_round_game();
+        assert_eq!(
+            game.check_lay_tile(5, 0, 7, 0),
+            Err(ActionError::InvalidUpgrade)
+        );
+        game.pub_coms
+            .get_mut(&PubComId::PRR)
+            .unwrap()
+            .remove_capital(750);
+        assert_eq!(
+            game.check_lay_tile(4, 2, 7, 0),
+            Err(ActionError::InsufficientCapital)
+        );
+        game.lay_tile(5, 0, 57, 1).unwrap();
+        game.phase = PhaseId::Phase3;
+        set_phase(&mut game, OperatingPhaseId::LayTrack);
+        assert_eq!(
+            game.check_lay_tile(5, 0, 15, 2),
+            Err(ActionError::InvalidUpgrade)
+        );
+        assert_eq!(
+            game.check_lay_tile(5, 0, 26, 1),
+            Err(ActionError::InvalidUpgrade)
+        );
+    }
+
+    #[test]
+    fn place_station_rejections() {
+        let mut game = operating_round_game();
+        game.lay_tile(5, 0, 57, 1).unwrap();
+        assert_eq!(
+            game.check_place_station(4, 1, 1),
+            Err(ActionError::InvalidCity)
+        );
+        assert_eq!(
+            game.check_place_station(5, 0, 0),
+            Err(ActionError::AlreadyStationed)
+        );
+        assert_eq!(
+            game.check_place_station(4, 1, 0),
+            Err(ActionError::CityNotReachable)
+        );
+        assert_eq!(
+            game.check_place_station(6, 6, 0),
+            Err(ActionError::CityNotReachable)
+        );
+        lay_hagerstown(&mut game);
+        let pub_com = game.pub_coms.get_mut(&PubComId::PRR).unwrap();
+        pub_com.remove_capital(770);
+        assert_eq!(
+            game.check_place_station(4, 1, 0),
+            Err(ActionError::InsufficientCapital)
+        );
+        game.map.place_station(PubComId::LV, 4, 1, 0);
+        assert_eq!(
+            game.check_place_station(4, 1, 0),
+            Err(ActionError::CityFull)
+        );
+        let pub_com = game.pub_coms.get_mut(&PubComId::PRR).unwrap();
+        for _ in 0..3 {
+            pub_com.use_station();
+        }
+        assert_eq!(
+            game.check_place_station(4, 1, 0),
+            Err(ActionError::NoStationsLeft)
+        );
+    }
+
+    #[test]
+    fn home_cities_are_reserved_until_floating() {
+        let map = include_str!("../../conf/map.toml")
+            .replace("id = \"LV\"\nx = 9\ny = 0", "id = \"LV\"\nx = 4\ny = 1");
+        let config = GameConfig::new().cv_pub_com(PubComId::SRR).map_str(map);
+        let mut game = Game::with_config(4, &config).unwrap();
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.enter_operating_round(0);
+        game.lay_tile(5, 0, 57, 1).unwrap();
+        lay_hagerstown(&mut game);
+        assert_eq!(
+            game.check_place_station(4, 1, 0),
+            Err(ActionError::CityReserved)
+        );
+    }
+
+    fn buy_trains_game() -> Game {
+        let mut game = operating_round_game();
+        set_phase(&mut game, OperatingPhaseId::BuyTrains);
+        game
+    }
+
+    fn add_train(game: &mut Game, pub_com: PubComId, train: TrainId) {
+        game.pub_coms.get_mut(&pub_com).unwrap().add_train(train);
+    }
+
+    fn remove_capital(game: &mut Game, pub_com: PubComId, capital: u32) {
+        game.pub_coms
+            .get_mut(&pub_com)
+            .unwrap()
+            .remove_capital(capital);
+    }
+
+    #[test]
+    fn buy_train_rejections() {
+        let mut game = buy_trains_game();
+        assert_eq!(game.check_pass(), Err(ActionError::MustBuyTrain));
+        add_train(&mut game, PubComId::PRR, TrainId::Range2);
+        remove_capital(&mut game, PubComId::PRR, 730);
+        assert_eq!(
+            game.check_buy_train(),
+            Err(ActionError::InsufficientCapital)
+        );
+        for _ in 0..3 {
+            add_train(&mut game, PubComId::PRR, TrainId::Range2);
+        }
+        assert_eq!(game.check_buy_train(), Err(ActionError::TrainLimit));
+    }
+
+    #[test]
+    fn president_funds_first_train() {
+        let mut game = buy_trains_game();
+        remove_capital(&mut game, PubComId::PRR, 770);
+        assert_eq!(game.buy_train(), Ok(()));
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 0);
+        assert_eq!(game.players[1].capital(), 550);
+
+        let mut game = buy_trains_game();
+        remove_capital(&mut game, PubComId::PRR, 770);
+        game.players[1].remove_capital(560);
+        assert_eq!(
+            game.check_buy_train(),
+            Err(ActionError::InsufficientCapital)
+        );
+    }
+
+    #[test]
+    fn new_train_type_changes_phase_and_rusts_trains() {
+        let mut game = buy_trains_game();
+        tests::float(&mut game, PubComId::LV, 95, 2);
+        add_train(&mut game, PubComId::PRR, TrainId::Range2);
+        for _ in 0..4 {
+            add_train(&mut game, PubComId::LV, TrainId::Range3);
+        }
+        for _ in 0..7 {
+            game.train_set.remove_train(TrainId::Range2);
+        }
+        assert_eq!(game.buy_train(), Ok(()));
+        assert_eq!(game.phase, PhaseId::Phase3);
+        for _ in 0..5 {
+            game.train_set.remove_train(TrainId::Range3);
+        }
+        assert_eq!(game.buy_train(), Ok(()));
+        assert_eq!(game.phase, PhaseId::Phase4);
+        assert_eq!(
+            game.pub_coms[&PubComId::PRR].trains(),
+            &[TrainId::Range3, TrainId::Range4]
+        );
+        assert_eq!(game.pub_coms[&PubComId::LV].trains().len(), 3);
+        assert_eq!(game.train_set.market(), &[TrainId::Range3]);
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 320);
+    }
+
+    #[test]
+    fn buy_market_train() {
+        let mut game = buy_trains_game();
+        assert_eq!(
+            game.check_buy_market_train(TrainId::Range3),
+            Err(ActionError::TrainUnavailable)
+        );
+        game.train_set.add_market_train(TrainId::Range3);
+        assert_eq!(game.buy_market_train(TrainId::Range3), Ok(()));
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 620);
+        assert!(game.train_set.market().is_empty());
+    }
+
+    #[test]
+    fn buy_pub_com_train() {
+        let mut game = buy_trains_game();
+        tests::float(&mut game, PubComId::LV, 95, 1);
+        add_train(&mut game, PubComId::LV, TrainId::Range3);
+        assert_eq!(
+            game.buy_pub_com_train(PubComId::LV, TrainId::Range3, 1),
+            Ok(())
+        );
+        assert_eq!(game.pub_coms[&PubComId::PRR].capital(), 799);
+        assert_eq!(game.pub_coms[&PubComId::LV].capital(), 951);
+        assert!(game.pub_coms[&PubComId::LV].trains().is_empty());
+    }
+
+    #[test]
+    fn buy_pub_com_train_rejections() {
+        let mut game = buy_trains_game();
+        tests::float(&mut game, PubComId::LV, 95, 1);
+        tests::float(&mut game, PubComId::CAndO, 95, 2);
+        add_train(&mut game, PubComId::PRR, TrainId::Range2);
+        add_train(&mut game, PubComId::LV, TrainId::Range3);
+        add_train(&mut game, PubComId::CAndO, TrainId::Range3);
+        assert_eq!(
+            game.check_buy_pub_com_train(PubComId::PRR, TrainId::Range2, 10),
+            Err(ActionError::TrainUnavailable)
+        );
+        assert_eq!(
+            game.check_buy_pub_com_train(PubComId::LV, TrainId::Range2, 10),
+            Err(ActionError::TrainUnavailable)
+        );
+        assert_eq!(
+            game.check_buy_pub_com_train(PubComId::CAndO, TrainId::Range3, 10),
+            Err(ActionError::NotSamePresident)
+        );
+        assert_eq!(
+            game.check_buy_pub_com_train(PubComId::LV, TrainId::Range3, 0),
+            Err(ActionError::InvalidPrice)
+        );
+        assert_eq!(
+            game.check_buy_pub_com_train(PubComId::LV, TrainId::Range3, 801),
+            Err(ActionError::InsufficientCapital)
+        );
+        for _ in 0..3 {
+            add_train(&mut game, PubComId::PRR, TrainId::Range2);
+        }
+        assert_eq!(
+            game.check_buy_pub_com_train(PubComId::LV, TrainId::Range3, 10),
+            Err(ActionError::TrainLimit)
+        );
+    }
+
+    #[test]
+    fn buy_train_from_empty_depot_is_rejected() {
+        let config = GameConfig::new().train_set_str("[[trains]]\ntrain = \"2\"\ncount = 1");
+        let mut game = Game::with_config(4, &config).unwrap();
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.enter_operating_round(0);
+        game.pass().unwrap();
+        game.pass().unwrap();
+        game.run_trains(false).unwrap();
+        game.buy_train().unwrap();
+        assert_eq!(game.check_buy_train(), Err(ActionError::NoTrainsAvailable));
+    }
 }
@@ -1,13 +1,13 @@
 use crate::economy::Player;
-use crate::{Game, PrivComId, RoundId};
+use crate::{ActionError, Game, PrivComId, RoundId};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-static ACTION_FORBIDDEN: &str = "action is forbidden";
-
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// Represents the auction for private companies
 pub struct PrivAuction {
     current: Option<PrivComId>,
+    #[serde(with = "bids_serde")]
     bids: Vec<HashMap<PrivComId, u32>>,
 }
 
@@ -27,7 +27,7 @@ impl PrivAuction {
                 if let Some(player) = self.player_with_max_bid(current) {
                     for i in 1..self.bids.len() {
                         let j = (player + i) % self.bids.len();
-                        if self.bids[j].get(&current).is_some() {
+                        if self.bids[j].contains_key(&current) {
                             return Some(j);
                         }
                     }
@@ -39,32 +39,11 @@ impl PrivAuction {
         }
     }
 
-    pub(crate) fn current_if_buy_allowed(&self, player: &Player) -> Option<PrivComId> {
-        if let Some(current) = self.current {
-            if current.cost() == self.max_bid(current)
-                && self.can_afford_bid(player, current, current.cost())
-            {
-                Some(current)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    }
-
-    pub(crate) fn current_if_pass_allowed(&self, player: &Player) -> Option<PrivComId> {
+    pub(crate) fn is_upcoming(&self, private: PrivComId) -> bool {
         if let Some(current) = self.current {
-            if current.cost() != 0
-                && (current.cost() == self.max_bid(current)
-                    || self.bids[player.id()].contains_key(&current))
-            {
-                Some(current)
-            } else {
-                None
-            }
+            private.cost() > current.cost() && PrivComId::values().skip(1).any(|p| p == private)
         } else {
-            None
+            false
         }
     }
 
@@ -95,7 +74,7 @@ impl PrivAuction {
     }
 
     pub(crate) fn can_afford_bid(&self, player: &Player, private: PrivComId, amount: u32) -> bool {
-        let previous_total_amount: u32 = self.bids[player.id()].iter().map(|(_, a)| a).sum();
+        let previous_total_amount: u32 = self.bids[player.id()].values().sum();
         if let Some(&bid) = self.bids[player.id()].get(&private) {
             amount + previous_total_amount - bid <= player.capital()
         } else {
@@ -157,34 +136,45 @@ impl PrivAuction {
 impl Game {
     /// Returns whether placing a bid of `amount` on `private` is allowed
     pub fn bid_priv_allowed(&self, private: PrivComId, amount: u32) -> bool {
+        self.check_bid_priv(private, amount).is_ok()
+    }
+
+    /// Returns `Ok` if placing a bid of `amount` on `private` is allowed, otherwise the reason
+    /// it is not
+    pub fn check_bid_priv(&self, private: PrivComId, amount: u32) -> Result<(), ActionError> {
         if let RoundId::PrivAuction(priv_auction) = &self.round {
-            if let Some(current_priv) = priv_auction.current() {
-                let current_player = &self.players[self.current_player];
-                priv_auction.can_afford_bid(&current_player, private, amount)
-                    && amount + 5 >= priv_auction.max_bid(private)
-                    && ((private == current_priv
-                        && priv_auction
-                            .bids(&current_player)
-                            .get(&private)
-                            .map_or(false, |&a| a != priv_auction.max_bid(private)))
-                        || (private != current_priv
-                            && current_priv.cost() == priv_auction.max_bid(current_priv)
-                            && !priv_auction
-                                .bids(&self.players[self.current_player])
-                                .contains_key(&private)))
+            let current_priv = priv_auction.current().ok_or(ActionError::AuctionOver)?;
+            let current_player = &self.players[self.current_player];
+            if private == current_priv {
+                match priv_auction.bids(current_player).get(&private) {
+                    None => return Err(ActionError::NotInAuction),
+                    Some(&bid) if bid == priv_auction.max_bid(private) => {
+                        return Err(ActionError::AlreadyHighestBidder);
+                    }
+                    _ => (),
+                }
+            } else if !priv_auction.is_upcoming(private) {
+                return Err(ActionError::PrivateUnavailable);
+            } else if current_priv.cost() != priv_auction.max_bid(current_priv) {
+                return Err(ActionError::AuctionInProgress);
+            } else if priv_auction.bids(current_player).contains_key(&private) {
+                return Err(ActionError::AlreadyBid);
+            }
+            if amount < priv_auction.max_bid(private) + 5 {
+                Err(ActionError::BidTooLow)
+            } else if !priv_auction.can_afford_bid(current_player, private, amount) {
+                Err(ActionError::InsufficientCapital)
             } else {
-                false
+                Ok(())
             }
         } else {
-            false
+            Err(ActionError::WrongRound)
         }
     }
 
     /// Places a bid of `amount` on `private`
-    pub fn bid_priv(&mut self, private: PrivComId, amount: u32) {
-        if !self.bid_priv_allowed(private, amount) {
-            panic!(ACTION_FORBIDDEN);
-        }
+    pub fn bid_priv(&mut self, private: PrivComId, amount: u32) -> Result<(), ActionError> {
+        self.check_bid_priv(private, amount)?;
         if let RoundId::PrivAuction(priv_auction) = &mut self.round {
             self.passes = 0;
             priv_auction.insert_bid(&self.players[self.current_player], private, amount);
@@ -193,39 +183,242 @@ impl Game {
             unreachable!();
         }
         self.advance_current_player();
+        Ok(())
     }
 
     /// Returns whether buying the cheapest private company is allowed
     pub fn buy_cheapest_priv_allowed(&self) -> bool {
+        self.check_buy_cheapest_priv().is_ok()
+    }
+
+    /// Returns `Ok` if buying the cheapest private company is allowed, otherwise the reason it is
+    /// not
+    pub fn check_buy_cheapest_priv(&self) -> Result<(), ActionError> {
         if let RoundId::PrivAuction(priv_auction) = &self.round {
-            priv_auction
-                .current_if_buy_allowed(&self.players[self.current_player])
-                .is_some()
+            let current_priv = priv_auction.current().ok_or(ActionError::AuctionOver)?;
+            if current_priv.cost() != priv_auction.max_bid(current_priv) {
+                Err(ActionError::AuctionInProgress)
+            } else if !priv_auction.can_afford_bid(
+                &self.players[self.current_player],
+                current_priv,
+                current_priv.cost(),
+            ) {
+                Err(ActionError::InsufficientCapital)
+            } else {
+                Ok(())
+            }
         } else {
-            false
+            Err(ActionError::WrongRound)
         }
     }
 
     /// Buys the cheapest private company
-    pub fn buy_cheapest_priv(&mut self) {
-        if !self.buy_cheapest_priv_allowed() {
-            panic!(ACTION_FORBIDDEN);
-        }
+    pub fn buy_cheapest_priv(&mut self) -> Result<(), ActionError> {
+        self.check_buy_cheapest_priv()?;
         if let RoundId::PrivAuction(priv_auction) = &mut self.round {
-            let current_player = &mut self.players[self.current_player];
-            if let Some(current_priv) = priv_auction.current_if_buy_allowed(&current_player) {
-                self.passes = 0;
-                priv_auction.advance_current();
-                current_player.buy_priv(current_priv, current_priv.cost());
-                self.priority_player = (self.current_player + 1) % self.players.len();
-                if priv_auction.current().is_none() {
-                    self.enter_first_stock_round();
-                    return;
-                }
+            let current_priv = priv_auction.current().unwrap();
+            self.passes = 0;
+            priv_auction.advance_current();
+            let auction_over = priv_auction.current().is_none();
+            self.sell_priv(self.current_player, current_priv, current_priv.cost());
+            self.priority_player = (self.current_player + 1) % self.players.len();
+            if auction_over {
+                self.enter_first_stock_round();
+                return Ok(());
             }
         } else {
             unreachable!();
         }
         self.advance_current_player();
+        Ok(())
+    }
+}
+
+/// Serializes bids as lists of pairs in the order of the private companies, since
+/// `PrivComId::DAndR` can not be a JSON object key
+mod bids_serde {
+    use crate::PrivComId;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::{BTreeMap, HashMap};
+
+    pub(super) fn serialize<S: Serializer>(
+        bids: &[HashMap<PrivComId, u32>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        bids.iter()
+            .map(|b| b.iter().collect::<BTreeMap<_, _>>())
+            .map(|b| b.into_iter().collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<HashMap<PrivComId, u32>>, D::Error> {
+        Vec::<Vec<(PrivComId, u32)>>::deserialize(deserializer)
+            .map(|bids| bids.into_iter().map(|b| b.into_iter().collect()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::tests;
+    use crate::{ActionError, Game, PrivComId, RoundId};
+
+    fn insert_bid(game: &mut Game, player: usize, private: PrivComId, amount: u32) {
+        if let RoundId::PrivAuction(priv_auction) = &mut game.round {
+            priv_auction.insert_bid(&game.players[player], private, amount);
+        }
+    }
+
+    fn advance_current(game: &mut Game) {
+        if let RoundId::PrivAuction(priv_auction) = &mut game.round {
+            priv_auction.advance_current();
+        }
+    }
+
+    #[test]
+    fn buy_cheapest_priv() {
+        let mut game = tests::game(4);
+        assert_eq!(game.buy_cheapest_priv(), Ok(()));
+        assert_eq!(game.players[0].capital(), 580);
+        assert_eq!(game.current_player(), 1);
+        assert_eq!(game.priority_player, 1);
+    }
+
+    #[test]
+    fn bid_priv_on_upcoming_private() {
+        let mut game = tests::game(4);
+        assert_eq!(game.bid_priv(PrivComId::BAndO, 105), Ok(()));
+        assert_eq!(game.current_player(), 1);
+    }
+
+    #[test]
+    fn actions_outside_auction_are_rejected() {
+        let game = tests::stock_round_game(4, false);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::CToP, 45),
+            Err(ActionError::WrongRound)
+        );
+        assert_eq!(game.check_buy_cheapest_priv(), Err(ActionError::WrongRound));
+    }
+
+    #[test]
+    fn actions_after_auction_are_rejected() {
+        let mut game = tests::game(4);
+        for _ in PrivComId::values() {
+            advance_current(&mut game);
+        }
+        assert_eq!(
+            game.check_bid_priv(PrivComId::CToP, 45),
+            Err(ActionError::AuctionOver)
+        );
+        assert_eq!(
+            game.check_buy_cheapest_priv(),
+            Err(ActionError::AuctionOver)
+        );
+        assert_eq!(game.check_pass(), Err(ActionError::AuctionOver));
+    }
+
+    #[test]
+    fn bid_on_current_private_without_bid_is_rejected() {
+        let mut game = tests::game(4);
+        advance_current(&mut game);
+        insert_bid(&mut game, 1, PrivComId::CToP, 50);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::CToP, 55),
+            Err(ActionError::NotInAuction)
+        );
+        assert_eq!(game.check_pass(), Err(ActionError::NotInAuction));
+    }
+
+    #[test]
+    fn bid_by_highest_bidder_is_rejected() {
+        let mut game = tests::game(4);
+        advance_current(&mut game);
+        insert_bid(&mut game, 0, PrivComId::CToP, 50);
+        insert_bid(&mut game, 1, PrivComId::CToP, 45);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::CToP, 55),
+            Err(ActionError::AlreadyHighestBidder)
+        );
+    }
+
+    #[test]
+    fn actions_during_auction_are_rejected() {
+        let mut game = tests::game(4);
+        advance_current(&mut game);
+        insert_bid(&mut game, 1, PrivComId::CToP, 50);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::BAndO, 105),
+            Err(ActionError::AuctionInProgress)
+        );
+        assert_eq!(
+            game.check_buy_cheapest_priv(),
+            Err(ActionError::AuctionInProgress)
+        );
+    }
+
+    #[test]
+    fn second_bid_on_private_is_rejected() {
+        let mut game = tests::game(4);
+        insert_bid(&mut game, 0, PrivComId::BAndO, 105);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::BAndO, 110),
+            Err(ActionError::AlreadyBid)
+        );
+    }
+
+    #[test]
+    fn low_bid_is_rejected() {
+        let mut game = tests::game(4);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::BAndO, 100),
+            Err(ActionError::BidTooLow)
+        );
+        insert_bid(&mut game, 1, PrivComId::BAndO, 110);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::BAndO, 110),
+            Err(ActionError::BidTooLow)
+        );
+        assert_eq!(game.check_bid_priv(PrivComId::BAndO, 115), Ok(()));
+    }
+
+    #[test]
+    fn bid_on_unavailable_private_is_rejected() {
+        let mut game = tests::game(4);
+        advance_current(&mut game);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::DAndR(20), 25),
+            Err(ActionError::PrivateUnavailable)
+        );
+        advance_current(&mut game);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::CToP, 45),
+            Err(ActionError::PrivateUnavailable)
+        );
+    }
+
+    #[test]
+    fn unaffordable_actions_are_rejected() {
+        let mut game = tests::game(4);
+        assert_eq!(
+            game.check_bid_priv(PrivComId::CV, 605),
+            Err(ActionError::InsufficientCapital)
+        );
+        game.players[0].remove_capital(600);
+        assert_eq!(
+            game.check_buy_cheapest_priv(),
+            Err(ActionError::InsufficientCapital)
+        );
+    }
+
+    #[test]
+    fn pass_on_free_private_is_rejected() {
+        let mut game = tests::game(4);
+        if let RoundId::PrivAuction(priv_auction) = &mut game.round {
+            priv_auction.reduce_d_and_r_price(5);
+        }
+        assert_eq!(game.check_pass(), Err(ActionError::MustBuyPrivate));
     }
 }
@@ -1,15 +1,16 @@
-use crate::economy::{Player, PubCom};
-use crate::{Game, PubComId, RoundId};
+use crate::economy::Player;
+use crate::{ActionError, Game, PubComId, RoundId};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-static ACTION_FORBIDDEN: &str = "action is forbidden";
-
 /// Represents a stock round
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StockRound {
     action_performed: bool,
     sell_allowed: bool,
+    #[serde(serialize_with = "crate::sorted_serde::set")]
     pub_coms_sold: HashSet<(PubComId, usize)>,
+    last_actor: Option<usize>,
 }
 
 impl StockRound {
@@ -18,6 +19,7 @@ impl StockRound {
             action_performed: false,
             sell_allowed,
             pub_coms_sold: HashSet::new(),
+            last_actor: None,
         }
     }
 
@@ -29,6 +31,10 @@ impl StockRound {
         self.action_performed = true;
     }
 
+    pub(crate) fn set_last_actor(&mut self, player: usize) {
+        self.last_actor = Some(player);
+    }
+
     pub(crate) fn unset_action_performed(&mut self) {
         self.action_performed = false;
     }
@@ -38,6 +44,16 @@ impl StockRound {
         self.action_performed
     }
 
+    /// Returns whether `player` has sold shares of `pub_com` in this `StockRound`
+    pub fn pub_com_sold(&self, pub_com: PubComId, player: usize) -> bool {
+        self.pub_coms_sold.contains(&(pub_com, player))
+    }
+
+    /// Returns the last player to perform an action in this `StockRound`, if any
+    pub fn last_actor(&self) -> Option<usize> {
+        self.last_actor
+    }
+
     /// Returns whether selling shares is allowed in this `StockRound`
     pub fn sell_allowed(&self) -> bool {
         self.sell_allowed
@@ -47,181 +63,526 @@ impl StockRound {
 impl Game {
     /// Returns whether buying a share of `pub_com` from the IPO is allowed
     pub fn buy_ipo_share_allowed(&self, pub_com: PubComId) -> bool {
-        if let RoundId::StockRound(_) = &self.round {
-            if let Some(par) = self.par_track.value(pub_com) {
-                let current_player = &self.players[self.current_player];
-                self.ipo.count(pub_com) > 0
-                    && current_player.shares().count(pub_com) < 6
-                    && self.certificate_count(current_player) < self.certificate_limit()
-                    && current_player.capital() >= par
+        self.check_buy_ipo_share(pub_com).is_ok()
+    }
+
+    /// Returns `Ok` if buying a share of `pub_com` from the IPO is allowed, otherwise the reason
+    /// it is not
+    pub fn check_buy_ipo_share(&self, pub_com: PubComId) -> Result<(), ActionError> {
+        if let RoundId::StockRound(stock_round) = &self.round {
+            let par = self
+                .par_track
+                .value(pub_com)
+                .ok_or(ActionError::NotParred)?;
+            if self.ipo.count(pub_com) == 0 {
+                Err(ActionError::NoSharesAvailable)
             } else {
-                false
+                self.check_buy_share(stock_round, pub_com, par)
             }
         } else {
-            false
+            Err(ActionError::WrongRound)
         }
     }
 
     /// Buys a share of `pub_com` from the IPO
-    pub fn buy_ipo_share(&mut self, pub_com: PubComId) {
-        if !self.buy_ipo_share_allowed(pub_com) {
-            panic!(ACTION_FORBIDDEN);
-        }
-        if let RoundId::StockRound(_) = &self.round {
-            if let Some(par) = self.par_track.value(pub_com) {
-                let current_player = &mut self.players[self.current_player];
-                self.ipo.remove_shares(pub_com, 1);
-                current_player.shares_mut().add_shares(pub_com, 1);
-                current_player.remove_capital(par);
-                self.update_president(pub_com);
-                if self.ipo.count(pub_com) < 5 {
-                    self.pub_coms
-                        .insert(pub_com, PubCom::new(pub_com, 10 * par));
-                    self.map.place_home_station(pub_com);
-                }
-            } else {
-                unreachable!();
-            }
-        } else {
-            unreachable!();
-        }
-        self.end_turn();
+    pub fn buy_ipo_share(&mut self, pub_com: PubComId) -> Result<(), ActionError> {
+        self.check_buy_ipo_share(pub_com)?;
+        let par = self.par_track.value(pub_com).unwrap();
+        let current_player = &mut self.players[self.current_player];
+        self.ipo.remove_shares(pub_com, 1);
+        current_player.shares_mut().add_shares(pub_com, 1);
+        current_player.remove_capital(par);
+        self.pay_ipo_share(pub_com, par);
+        self.update_president(pub_com);
+        self.float_pub_com(pub_com);
+        self.finish_turn();
+        Ok(())
     }
 
     /// Returns whether buying a share of `pub_com` from the bank pool is allowed
     pub fn buy_pool_share_allowed(&self, pub_com: PubComId) -> bool {
-        if let RoundId::StockRound(_) = &self.round {
-            if let Some(value) = self.stock_chart.value(pub_com) {
-                let current_player = &self.players[self.current_player];
-                self.pool.count(pub_com) > 0
-                    && current_player.shares().count(pub_com) < 6
-                    && self.certificate_count(current_player) < self.certificate_limit()
-                    && current_player.capital() >= value
+        self.check_buy_pool_share(pub_com).is_ok()
+    }
+
+    /// Returns `Ok` if buying a share of `pub_com` from the bank pool is allowed, otherwise the
+    /// reason it is not
+    pub fn check_buy_pool_share(&self, pub_com: PubComId) -> Result<(), ActionError> {
+        if let RoundId::StockRound(stock_round) = &self.round {
+            let value = self
+                .stock_chart
+                .value(pub_com)
+                .ok_or(ActionError::NotParred)?;
+            if self.pool.count(pub_com) == 0 {
+                Err(ActionError::NoSharesAvailable)
             } else {
-                false
+                self.check_buy_share(stock_round, pub_com, value)
             }
         } else {
-            false
+            Err(ActionError::WrongRound)
         }
     }
 
     /// Buys a share of `pub_com` from the bank pool
-    pub fn buy_pool_share(&mut self, pub_com: PubComId) {
-        if !self.buy_pool_share_allowed(pub_com) {
-            panic!(ACTION_FORBIDDEN);
-        }
-        if let RoundId::StockRound(_) = &self.round {
-            if let Some(value) = self.stock_chart.value(pub_com) {
-                let current_player = &mut self.players[self.current_player];
-                self.pool.remove_shares(pub_com, 1);
-                current_player.shares_mut().add_shares(pub_com, 1);
-                current_player.remove_capital(value);
-                self.update_president(pub_com);
-            } else {
-                unreachable!();
-            }
-        } else {
-            unreachable!();
-        }
-        self.end_turn();
+    pub fn buy_pool_share(&mut self, pub_com: PubComId) -> Result<(), ActionError> {
+        self.check_buy_pool_share(pub_com)?;
+        let value = self.stock_chart.value(pub_com).unwrap();
+        let current_player = &mut self.players[self.current_player];
+        self.pool.remove_shares(pub_com, 1);
+        current_player.shares_mut().add_shares(pub_com, 1);
+        current_player.remove_capital(value);
+        self.bank.deposit(value);
+        self.update_president(pub_com);
+        self.finish_turn();
+        Ok(())
     }
 
     /// Returns whether buying the precidency of `pub_com`, setting the par value to `par` is allowed
     pub fn buy_presidency_allowed(&self, pub_com: PubComId, par: u32) -> bool {
+        self.check_buy_presidency(pub_com, par).is_ok()
+    }
+
+    /// Returns `Ok` if buying the precidency of `pub_com`, setting the par value to `par` is
+    /// allowed, otherwise the reason it is not
+    pub fn check_buy_presidency(&self, pub_com: PubComId, par: u32) -> Result<(), ActionError> {
         if let RoundId::StockRound(_) = &self.round {
             let current_player = &self.players[self.current_player];
-            self.par_track.values().contains(&par)
-                && self.ipo.contains_presidency(pub_com)
-                && self.certificate_count(current_player) < self.certificate_limit()
-                && current_player.capital() >= par * 2
+            if !self.ipo.contains_presidency(pub_com) {
+                Err(ActionError::AlreadyParred)
+            } else if !self.par_track.values().contains(&par) {
+                Err(ActionError::InvalidParValue)
+            } else if self.certificate_count(current_player) >= self.certificate_limit() {
+                Err(ActionError::CertificateLimit)
+            } else if current_player.capital() < par * 2 {
+                Err(ActionError::InsufficientCapital)
+            } else {
+                Ok(())
+            }
         } else {
-            false
+            Err(ActionError::WrongRound)
         }
     }
 
     /// Buys the precidency of `pub_com`, setting the par value to `par`
-    pub fn buy_presidency(&mut self, pub_com: PubComId, par: u32) {
-        if !self.buy_presidency_allowed(pub_com, par) {
-            panic!(ACTION_FORBIDDEN);
-        }
+    pub fn buy_presidency(&mut self, pub_com: PubComId, par: u32) -> Result<(), ActionError> {
+        self.check_buy_presidency(pub_com, par)?;
+        let current_player = &mut self.players[self.current_player];
+        self.ipo.remove_presidency(pub_com);
+        current_player.shares_mut().add_shares(pub_com, 2);
+        current_player.shares_mut().add_presidency(pub_com);
+        current_player.remove_capital(par * 2);
+        self.bank.deposit(par * 2);
+        self.par_track.add_token(pub_com, par);
+        self.stock_chart.add_token(pub_com, par);
+        self.float_pub_com(pub_com);
+        self.finish_turn();
+        Ok(())
+    }
+
+    /// Returns whether setting the par value of `pub_com` to `par` is allowed
+    pub fn set_par_allowed(&self, pub_com: PubComId, par: u32) -> bool {
+        self.check_set_par(pub_com, par).is_ok()
+    }
+
+    /// Returns `Ok` if setting the par value of `pub_com`, the presidency of which came with a
+    /// private company, to `par` is allowed, otherwise the reason it is not
+    pub fn check_set_par(&self, pub_com: PubComId, par: u32) -> Result<(), ActionError> {
         if let RoundId::StockRound(_) = &self.round {
-            let current_player = &mut self.players[self.current_player];
-            self.pool.remove_shares(pub_com, 2);
-            self.pool.remove_presidency(pub_com);
-            current_player.shares_mut().add_shares(pub_com, 2);
-            current_player.shares_mut().add_presidency(pub_com);
-            current_player.remove_capital(par * 2);
-            self.par_track.add_token(pub_com, par);
-            self.stock_chart.add_token(pub_com, par);
+            let current_player = &self.players[self.current_player];
+            if !current_player.shares().contains_presidency(pub_com) {
+                Err(ActionError::NotPresident)
+            } else if self.par_track.value(pub_com).is_some() {
+                Err(ActionError::AlreadyParred)
+            } else if !self.par_track.values().contains(&par) {
+                Err(ActionError::InvalidParValue)
+            } else {
+                Ok(())
+            }
         } else {
-            unreachable!();
+            Err(ActionError::WrongRound)
         }
-        self.end_turn();
+    }
+
+    /// Sets the par value of `pub_com` to `par` without ending the turn
+    pub fn set_par(&mut self, pub_com: PubComId, par: u32) -> Result<(), ActionError> {
+        self.check_set_par(pub_com, par)?;
+        self.par_track.add_token(pub_com, par);
+        self.stock_chart.add_token(pub_com, par);
+        self.float_pub_com(pub_com);
+        if let RoundId::StockRound(stock_round) = &mut self.round {
+            stock_round.set_last_actor(self.current_player);
+        }
+        Ok(())
     }
 
     /// Returns whether ending the turn is allowed
     pub fn end_turn_allowed(&self) -> bool {
+        self.check_end_turn().is_ok()
+    }
+
+    /// Returns `Ok` if ending the turn is allowed, otherwise the reason it is not
+    pub fn check_end_turn(&self) -> Result<(), ActionError> {
         if let RoundId::StockRound(stock_round) = &self.round {
-            stock_round.action_performed()
-                && self.certificate_count(&self.players[self.current_player])
-                    <= self.certificate_limit()
+            if !stock_round.action_performed() {
+                Err(ActionError::NoActionPerformed)
+            } else if self.certificate_count(&self.players[self.current_player])
+                > self.certificate_limit()
+            {
+                Err(ActionError::CertificateLimit)
+            } else {
+                Ok(())
+            }
         } else {
-            false
+            Err(ActionError::WrongRound)
         }
     }
 
     /// Ends the turn
-    pub fn end_turn(&mut self) {
-        if !self.end_turn_allowed() {
-            panic!(ACTION_FORBIDDEN);
-        }
-        if let RoundId::StockRound(stock_round) = &mut self.round {
-            stock_round.unset_action_performed();
-            self.passes = 0;
-        } else {
-            unreachable!();
-        }
-        self.advance_current_player();
+    pub fn end_turn(&mut self) -> Result<(), ActionError> {
+        self.check_end_turn()?;
+        self.finish_turn();
+        Ok(())
     }
 
     /// Returns whether selling `count` shares of `pub_com` is allowed
     pub fn sell_shares_allowed(&self, pub_com: PubComId, count: u32) -> bool {
+        self.check_sell_shares(pub_com, count).is_ok()
+    }
+
+    /// Returns `Ok` if selling `count` shares of `pub_com` is allowed, otherwise the reason it is
+    /// not
+    pub fn check_sell_shares(&self, pub_com: PubComId, count: u32) -> Result<(), ActionError> {
         if let RoundId::StockRound(stock_round) = &self.round {
             let current_player = &self.players[self.current_player];
             let owned_count = current_player.shares().count(pub_com);
-            let president = current_player.shares().contains_presidency(pub_com);
-            (!president
-                || owned_count - count >= 2
-                || self
+            if !stock_round.sell_allowed() {
+                Err(ActionError::SellingNotAllowed)
+            } else if self.stock_chart.value(pub_com).is_none() {
+                Err(ActionError::NotParred)
+            } else if count == 0 || owned_count < count {
+                Err(ActionError::NotEnoughShares)
+            } else if count + self.pool.count(pub_com) > 5 {
+                Err(ActionError::PoolShareLimit)
+            } else if current_player.shares().contains_presidency(pub_com)
+                && owned_count - count < 2
+                && !self
                     .players
                     .iter()
-                    .any(|p| p.id() != current_player.id() && p.shares().count(pub_com) >= 2))
-                && count + self.pool.count(pub_com) <= 5
-                && owned_count >= count
-                && self.stock_chart.value(pub_com).is_some()
-                && stock_round.sell_allowed()
+                    .any(|p| p.id() != current_player.id() && p.shares().count(pub_com) >= 2)
+            {
+                Err(ActionError::PresidencyDump)
+            } else {
+                Ok(())
+            }
         } else {
-            false
+            Err(ActionError::WrongRound)
         }
     }
 
     /// Sells `count` shares of `pub_com`
-    pub fn sell_shares(&mut self, pub_com: PubComId, count: u32) {
-        if !self.sell_shares_allowed(pub_com, count) {
-            panic!(ACTION_FORBIDDEN);
-        }
+    pub fn sell_shares(&mut self, pub_com: PubComId, count: u32) -> Result<(), ActionError> {
+        self.check_sell_shares(pub_com, count)?;
         if let RoundId::StockRound(stock_round) = &mut self.round {
             let current_player = &mut self.players[self.current_player];
             stock_round.set_action_performed();
             stock_round.insert_pub_com_sold(pub_com, current_player);
             current_player.shares_mut().remove_shares(pub_com, count);
             self.pool.add_shares(pub_com, count);
-            current_player.add_capital(self.stock_chart.value(pub_com).unwrap() * count);
+            let value = self.stock_chart.value(pub_com).unwrap() * count;
+            self.bank.withdraw(value);
+            current_player.add_capital(value);
             self.stock_chart.move_down(pub_com, count as usize);
             self.update_president(pub_com);
         } else {
             unreachable!();
         }
+        Ok(())
+    }
+
+    fn check_buy_share(
+        &self,
+        stock_round: &StockRound,
+        pub_com: PubComId,
+        price: u32,
+    ) -> Result<(), ActionError> {
+        let current_player = &self.players[self.current_player];
+        if stock_round.pub_com_sold(pub_com, self.current_player) {
+            Err(ActionError::SoldThisRound)
+        } else if current_player.shares().count(pub_com) >= 6 {
+            Err(ActionError::HoldingLimit)
+        } else if self.certificate_count(current_player) >= self.certificate_limit() {
+            Err(ActionError::CertificateLimit)
+        } else if current_player.capital() < price {
+            Err(ActionError::InsufficientCapital)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn finish_stock_round(&mut self) {
+        if let RoundId::StockRound(stock_round) = &self.round {
+            if let Some(last_actor) = stock_round.last_actor() {
+                self.priority_player = (last_actor + 1) % self.players.len();
+            }
+        } else {
+            unreachable!();
+        }
+        for pub_com in PubComId::values() {
+            if self.stock_chart.value(pub_com).is_some()
+                && self.ipo.count(pub_com) == 0
+                && self.pool.count(pub_com) == 0
+            {
+                self.stock_chart.move_up(pub_com);
+            }
+        }
+        self.passes = 0;
+        self.enter_operating_round(self.phase.operating_round_count() - 1);
+    }
+
+    fn finish_turn(&mut self) {
+        if let RoundId::StockRound(stock_round) = &mut self.round {
+            stock_round.set_last_actor(self.current_player);
+            stock_round.unset_action_performed();
+            self.passes = 0;
+        } else {
+            unreachable!();
+        }
         self.advance_current_player();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::game::tests::{self, par};
+    use crate::{ActionError, PrivComId, PubComId, RoundId};
+
+    #[test]
+    fn buy_ipo_share() {
+        let mut game = tests::stock_round_game(4, false);
+        par(&mut game, PubComId::PRR, 80, 1);
+        assert_eq!(game.buy_ipo_share(PubComId::PRR), Ok(()));
+        assert_eq!(game.players[0].capital(), 520);
+        assert_eq!(game.players[0].shares().count(PubComId::PRR), 1);
+        assert_eq!(game.ipo.count(PubComId::PRR), 7);
+        assert_eq!(game.current_player(), 1);
+    }
+
+    #[test]
+    fn buy_ipo_share_rejections() {
+        let game = tests::game(4);
+        assert_eq!(
+            game.check_buy_ipo_share(PubComId::PRR),
+            Err(ActionError::WrongRound)
+        );
+        let mut game = tests::stock_round_game(4, false);
+        assert_eq!(
+            game.check_buy_ipo_share(PubComId::PRR),
+            Err(ActionError::NotParred)
+        );
+        par(&mut game, PubComId::PRR, 80, 1);
+        game.players[0].remove_capital(550);
+        assert_eq!(
+            game.check_buy_ipo_share(PubComId::PRR),
+            Err(ActionError::InsufficientCapital)
+        );
+        game.players[0].shares_mut().add_shares(PubComId::LV, 16);
+        assert_eq!(
+            game.check_buy_ipo_share(PubComId::PRR),
+            Err(ActionError::CertificateLimit)
+        );
+        game.players[0].shares_mut().add_shares(PubComId::PRR, 6);
+        assert_eq!(
+            game.check_buy_ipo_share(PubComId::PRR),
+            Err(ActionError::HoldingLimit)
+        );
+        game.ipo.remove_shares(PubComId::PRR, 8);
+        assert_eq!(
+            game.check_buy_ipo_share(PubComId::PRR),
+            Err(ActionError::NoSharesAvailable)
+        );
+    }
+
+    #[test]
+    fn buy_pool_share() {
+        let mut game = tests::stock_round_game(4, false);
+        par(&mut game, PubComId::PRR, 80, 1);
+        assert_eq!(
+            game.check_buy_pool_share(PubComId::PRR),
+            Err(ActionError::NoSharesAvailable)
+        );
+        game.pool.add_shares(PubComId::PRR, 1);
+        assert_eq!(game.buy_pool_share(PubComId::PRR), Ok(()));
+        assert_eq!(game.players[0].capital(), 520);
+        assert_eq!(game.pool.count(PubComId::PRR), 0);
+    }
+
+    #[test]
+    fn set_par() {
+        let mut game = tests::stock_round_game(4, false);
+        game.sell_priv(0, PrivComId::BAndO, 100);
+        assert_eq!(game.set_par(PubComId::BAndO, 95), Ok(()));
+        assert_eq!(game.par_track.value(PubComId::BAndO), Some(95));
+        assert_eq!(game.stock_chart.value(PubComId::BAndO), Some(95));
+        assert_eq!(game.current_player(), 0);
+        assert!(matches!(&game.round, RoundId::StockRound(s) if s.last_actor() == Some(0)));
+    }
+
+    #[test]
+    fn set_par_rejections() {
+        let mut game = tests::game(4);
+        game.sell_priv(0, PrivComId::BAndO, 100);
+        assert_eq!(
+            game.check_set_par(PubComId::BAndO, 95),
+            Err(ActionError::WrongRound)
+        );
+        let mut game = tests::stock_round_game(4, false);
+        assert_eq!(
+            game.check_set_par(PubComId::BAndO, 95),
+            Err(ActionError::NotPresident)
+        );
+        game.sell_priv(0, PrivComId::BAndO, 100);
+        assert_eq!(
+            game.check_set_par(PubComId::BAndO, 75),
+            Err(ActionError::InvalidParValue)
+        );
+        game.set_par(PubComId::BAndO, 95).unwrap();
+        assert_eq!(
+            game.check_set_par(PubComId::BAndO, 95),
+            Err(ActionError::AlreadyParred)
+        );
+    }
+
+    #[test]
+    fn buy_presidency() {
+        let mut game = tests::stock_round_game(4, false);
+        assert_eq!(game.buy_presidency(PubComId::LV, 95), Ok(()));
+        assert!(game.players[0].shares().contains_presidency(PubComId::LV));
+        assert_eq!(game.players[0].shares().count(PubComId::LV), 2);
+        assert_eq!(game.players[0].capital(), 410);
+        assert!(!game.ipo.contains_presidency(PubComId::LV));
+        assert_eq!(game.ipo.count(PubComId::LV), 8);
+        assert_eq!(game.par_track.value(PubComId::LV), Some(95));
+        assert_eq!(game.current_player(), 1);
+    }
+
+    #[test]
+    fn buy_presidency_rejections() {
+        let mut game = tests::stock_round_game(4, false);
+        par(&mut game, PubComId::PRR, 80, 1);
+        assert_eq!(
+            game.check_buy_presidency(PubComId::PRR, 80),
+            Err(ActionError::AlreadyParred)
+        );
+        assert_eq!(
+            game.check_buy_presidency(PubComId::LV, 75),
+            Err(ActionError::InvalidParValue)
+        );
+        assert_eq!(game.check_buy_presidency(PubComId::LV, 95), Ok(()));
+        game.players[0].remove_capital(450);
+        assert_eq!(
+            game.check_buy_presidency(PubComId::LV, 80),
+            Err(ActionError::InsufficientCapital)
+        );
+        game.players[0].shares_mut().add_shares(PubComId::CAndA, 16);
+        assert_eq!(
+            game.check_buy_presidency(PubComId::LV, 70),
+            Err(ActionError::CertificateLimit)
+        );
+    }
+
+    #[test]
+    fn sell_shares() {
+        let mut game = tests::stock_round_game(4, true);
+        par(&mut game, PubComId::PRR, 80, 0);
+        game.players[0].shares_mut().add_shares(PubComId::PRR, 2);
+        assert_eq!(game.sell_shares(PubComId::PRR, 2), Ok(()));
+        assert_eq!(game.players[0].capital(), 760);
+        assert_eq!(game.players[0].shares().count(PubComId::PRR), 2);
+        assert_eq!(game.pool.count(PubComId::PRR), 2);
+        assert_eq!(game.current_player(), 0);
+        assert_eq!(game.end_turn(), Ok(()));
+        assert_eq!(game.current_player(), 1);
+    }
+
+    #[test]
+    fn sell_shares_rejections() {
+        let mut game = tests::stock_round_game(4, false);
+        par(&mut game, PubComId::PRR, 80, 0);
+        assert_eq!(
+            game.check_sell_shares(PubComId::PRR, 1),
+            Err(ActionError::SellingNotAllowed)
+        );
+        let mut game = tests::stock_round_game(4, true);
+        assert_eq!(
+            game.check_sell_shares(PubComId::PRR, 1),
+            Err(ActionError::NotParred)
+        );
+        par(&mut game, PubComId::PRR, 80, 0);
+        game.players[0].shares_mut().add_shares(PubComId::PRR, 4);
+        assert_eq!(
+            game.check_sell_shares(PubComId::PRR, 0),
+            Err(ActionError::NotEnoughShares)
+        );
+        assert_eq!(
+            game.check_sell_shares(PubComId::PRR, 7),
+            Err(ActionError::NotEnoughShares)
+        );
+        assert_eq!(
+            game.check_sell_shares(PubComId::PRR, 5),
+            Err(ActionError::PresidencyDump)
+        );
+        game.pool.add_shares(PubComId::PRR, 4);
+        assert_eq!(
+            game.check_sell_shares(PubComId::PRR, 2),
+            Err(ActionError::PoolShareLimit)
+        );
+    }
+
+    #[test]
+    fn end_turn_rejections() {
+        let game = tests::game(4);
+        assert_eq!(game.check_end_turn(), Err(ActionError::WrongRound));
+        let mut game = tests::stock_round_game(4, true);
+        assert_eq!(game.check_end_turn(), Err(ActionError::NoActionPerformed));
+        par(&mut game, PubComId::PRR, 80, 0);
+        game.players[0].shares_mut().add_shares(PubComId::PRR, 1);
+        game.players[1].shares_mut().add_shares(PubComId::PRR, 2);
+        game.players[0].shares_mut().add_shares(PubComId::LV, 16);
+        game.sell_shares(PubComId::PRR, 1).unwrap();
+        assert_eq!(game.check_end_turn(), Err(ActionError::CertificateLimit));
+    }
+
+    #[test]
+    fn buying_after_selling_is_rejected() {
+        let mut game = tests::stock_round_game(4, true);
+        par(&mut game, PubComId::PRR, 80, 0);
+        game.players[0].shares_mut().add_shares(PubComId::PRR, 2);
+        game.pool.add_shares(PubComId::PRR, 1);
+        game.sell_shares(PubComId::PRR, 1).unwrap();
+        assert_eq!(
+            game.check_buy_ipo_share(PubComId::PRR),
+            Err(ActionError::SoldThisRound)
+        );
+        assert_eq!(
+            game.check_buy_pool_share(PubComId::PRR),
+            Err(ActionError::SoldThisRound)
+        );
+        game.end_turn().unwrap();
+        assert_eq!(game.check_buy_ipo_share(PubComId::PRR), Ok(()));
+    }
+
+    #[test]
+    fn finish_stock_round() {
+        let mut game = tests::stock_round_game(4, false);
+        par(&mut game, PubComId::PRR, 80, 1);
+        par(&mut game, PubComId::LV, 80, 2);
+        game.ipo.remove_shares(PubComId::PRR, 7);
+        assert_eq!(game.buy_ipo_share(PubComId::PRR), Ok(()));
+        for _ in 0..4 {
+            assert_eq!(game.pass(), Ok(()));
+        }
+        assert!(matches!(game.round, RoundId::OperatingRound(_)));
+        assert_eq!(game.priority_player, 1);
+        assert_eq!(game.stock_chart.value(PubComId::PRR), Some(85));
+        assert_eq!(game.stock_chart.value(PubComId::LV), Some(80));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{Game, SnapshotError};
+use serde::{Deserialize, Serialize};
+
+/// The version of the snapshots written by this library
+pub static SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    version: u32,
+    game: &'a Game,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    game: Game,
+}
+
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+impl Game {
+    /// Returns a JSON snapshot of this `Game`
+    ///
+    /// Snapshots hold the state of the game at its start as well, so that actions applied before
+    /// saving can still be undone after loading, at the cost of roughly doubling their size.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.snapshot()).unwrap()
+    }
+
+    /// Returns the `Game` in the JSON snapshot `s`
+    pub fn from_json(s: &str) -> Result<Self, SnapshotError> {
+        let version: Version = serde_json::from_str(s).map_err(malformed)?;
+        check_version(version.version)?;
+        let snapshot: Snapshot = serde_json::from_str(s).map_err(malformed)?;
+        Ok(snapshot.game)
+    }
+
+    /// Returns a compact binary snapshot of this `Game`, holding the same state as `to_json`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self.snapshot()).unwrap()
+    }
+
+    /// Returns the `Game` in the binary snapshot `bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let version: u32 = bincode::deserialize(bytes).map_err(malformed)?;
+        check_version(version)?;
+        let (_, game): (u32, Game) = bincode::deserialize(bytes).map_err(malformed)?;
+        Ok(game)
+    }
+
+    fn snapshot(&self) -> SnapshotRef<'_> {
+        SnapshotRef {
+            version: SNAPSHOT_VERSION,
+            game: self,
+        }
+    }
+}
+
+fn check_version(version: u32) -> Result<(), SnapshotError> {
+    if version == SNAPSHOT_VERSION {
+        Ok(())
+    } else {
+        Err(SnapshotError::UnsupportedVersion(version))
+    }
+}
+
+fn malformed(err: impl ToString) -> SnapshotError {
+    SnapshotError::Malformed(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SNAPSHOT_VERSION;
+    use crate::game::tests;
+    use crate::{Game, PrivComId, PubComId, SnapshotError};
+
+    fn auction_game() -> Game {
+        let mut game = tests::game(4);
+        game.buy_cheapest_priv().unwrap();
+        game.bid_priv(PrivComId::BAndO, 105).unwrap();
+        game
+    }
+
+    fn operating_round_game() -> Game {
+        let mut game = tests::game(4);
+        tests::float(&mut game, PubComId::PRR, 80, 1);
+        game.enter_operating_round(0);
+        game.lay_tile(5, 0, 57, 1).unwrap();
+        game
+    }
+
+    #[test]
+    fn json_snapshots_round_trip() {
+        for game in [auction_game(), operating_round_game()] {
+            assert_eq!(Game::from_json(&game.to_json()), Ok(game));
+        }
+    }
+
+    #[test]
+    fn json_snapshots_are_stable() {
+        let json = operating_round_game().to_json();
+        assert_eq!(Game::from_json(&json).unwrap().to_json(), json);
+    }
+
+    #[test]
+    fn binary_snapshots_round_trip() {
+        for game in [auction_game(), operating_round_game()] {
+            assert_eq!(Game::from_bytes(&game.to_bytes()), Ok(game));
+        }
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let game = auction_game();
+        let version = SNAPSHOT_VERSION + 1;
+        let json = game.to_json().replacen(
+            &format!("\"version\":{}", SNAPSHOT_VERSION),
+            &format!("\"version\":{}", version),
+            1,
+        );
+        assert_eq!(
+            Game::from_json(&json),
+            Err(SnapshotError::UnsupportedVersion(version))
+        );
+        let mut bytes = game.to_bytes();
+        bytes[..4].copy_from_slice(&version.to_le_bytes());
+        assert_eq!(
+            Game::from_bytes(&bytes),
+            Err(SnapshotError::UnsupportedVersion(version))
+        );
+    }
+
+    #[test]
+    fn malformed_snapshots_are_rejected() {
+        assert!(matches!(
+            Game::from_json("{}"),
+            Err(SnapshotError::Malformed(_))
+        ));
+        assert!(matches!(
+            Game::from_bytes(&[]),
+            Err(SnapshotError::Malformed(_))
+        ));
+    }
+}
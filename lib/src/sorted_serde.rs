@@ -0,0 +1,18 @@
+//! Serializes hash maps and sets in the order of their keys, so that equal games serialize alike
+
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+pub(crate) fn map<K: Ord + Serialize, V: Serialize, S: Serializer>(
+    map: &HashMap<K, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+pub(crate) fn set<T: Ord + Serialize, S: Serializer>(
+    set: &HashSet<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    set.iter().collect::<BTreeSet<_>>().serialize(serializer)
+}
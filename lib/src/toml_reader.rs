@@ -0,0 +1,180 @@
+use crate::ConfigError;
+use std::fmt::Display;
+use std::str::FromStr;
+use toml::Value;
+
+/// Reads values from a TOML document, collecting a `ConfigError` for every problem found
+#[derive(Debug)]
+pub(crate) struct TomlReader {
+    file: String,
+    errors: Vec<ConfigError>,
+}
+
+impl TomlReader {
+    pub(crate) fn new(file: &str) -> Self {
+        Self {
+            file: file.to_string(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub(crate) fn parse(&mut self, s: &str) -> Option<Value> {
+        match s.parse::<Value>() {
+            Ok(toml) => Some(toml),
+            Err(err) => {
+                self.error("", format!("is not valid TOML: {}", err));
+                None
+            }
+        }
+    }
+
+    pub(crate) fn error(&mut self, path: &str, problem: String) {
+        self.errors
+            .push(ConfigError::new(&self.file, path, problem));
+    }
+
+    pub(crate) fn finish<T>(self, value: Option<T>) -> Result<T, Vec<ConfigError>> {
+        match value {
+            Some(value) if self.errors.is_empty() => Ok(value),
+            _ => Err(self.errors),
+        }
+    }
+
+    pub(crate) fn get<'a>(&mut self, toml: &'a Value, path: &str, key: &str) -> Option<&'a Value> {
+        let value = toml.get(key);
+        if value.is_none() {
+            self.error(&key_path(path, key), "is missing".to_string());
+        }
+        value
+    }
+
+    pub(crate) fn integer(&mut self, toml: &Value, path: &str, key: &str) -> Option<i64> {
+        let value = self.get(toml, path, key)?;
+        self.as_integer(value, &key_path(path, key))
+    }
+
+    pub(crate) fn unsigned(&mut self, toml: &Value, path: &str, key: &str) -> Option<u32> {
+        let value = self.get(toml, path, key)?;
+        self.as_unsigned(value, &key_path(path, key))
+    }
+
+    pub(crate) fn opt_unsigned(&mut self, toml: &Value, path: &str, key: &str) -> Option<u32> {
+        let value = toml.get(key)?;
+        self.as_unsigned(value, &key_path(path, key))
+    }
+
+    pub(crate) fn string<'a>(&mut self, toml: &'a Value, path: &str, key: &str) -> Option<&'a str> {
+        let value = self.get(toml, path, key)?;
+        self.as_str(value, &key_path(path, key))
+    }
+
+    pub(crate) fn opt_string<'a>(
+        &mut self,
+        toml: &'a Value,
+        path: &str,
+        key: &str,
+    ) -> Option<&'a str> {
+        let value = toml.get(key)?;
+        self.as_str(value, &key_path(path, key))
+    }
+
+    pub(crate) fn parsed<T>(&mut self, toml: &Value, path: &str, key: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self.string(toml, path, key)?;
+        self.parse_str(value, &key_path(path, key))
+    }
+
+    pub(crate) fn opt_parsed<T>(&mut self, toml: &Value, path: &str, key: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self.opt_string(toml, path, key)?;
+        self.parse_str(value, &key_path(path, key))
+    }
+
+    pub(crate) fn array<'a>(
+        &mut self,
+        toml: &'a Value,
+        path: &str,
+        key: &str,
+    ) -> Option<&'a [Value]> {
+        let value = self.get(toml, path, key)?;
+        self.as_array(value, &key_path(path, key))
+    }
+
+    pub(crate) fn opt_array<'a>(
+        &mut self,
+        toml: &'a Value,
+        path: &str,
+        key: &str,
+    ) -> Option<&'a [Value]> {
+        let value = toml.get(key)?;
+        self.as_array(value, &key_path(path, key))
+    }
+
+    pub(crate) fn as_integer(&mut self, value: &Value, path: &str) -> Option<i64> {
+        let value = value.as_integer();
+        if value.is_none() {
+            self.error(path, "is not of type Integer".to_string());
+        }
+        value
+    }
+
+    pub(crate) fn as_unsigned(&mut self, value: &Value, path: &str) -> Option<u32> {
+        let value = self.as_integer(value, path)?;
+        if value < 0 || value > i64::from(u32::MAX) {
+            self.error(path, format!("is out of range: {}", value));
+            None
+        } else {
+            Some(value as u32)
+        }
+    }
+
+    pub(crate) fn as_str<'a>(&mut self, value: &'a Value, path: &str) -> Option<&'a str> {
+        let value = value.as_str();
+        if value.is_none() {
+            self.error(path, "is not of type String".to_string());
+        }
+        value
+    }
+
+    pub(crate) fn as_array<'a>(&mut self, value: &'a Value, path: &str) -> Option<&'a [Value]> {
+        let value = value.as_array().map(|a| a.as_slice());
+        if value.is_none() {
+            self.error(path, "is not of type Array".to_string());
+        }
+        value
+    }
+
+    fn parse_str<T>(&mut self, value: &str, path: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match value.parse::<T>() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.error(path, format!("is invalid: {}", err));
+                None
+            }
+        }
+    }
+}
+
+/// Returns the TOML path of `key` in the table at `path`
+pub(crate) fn key_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Returns the TOML path of element `index` in the array at `path`
+pub(crate) fn index_path(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
@@ -1,41 +1,99 @@
-use crate::TrainId;
-use crate::INVALID_TOML;
-use std::collections::HashSet;
+use crate::toml_reader::{index_path, key_path, TomlReader};
+use crate::{ConfigError, TrainId};
+use serde::{Deserialize, Serialize};
 use toml::Value;
 
-static TRAINS_MISSING: &str = "trains is missing";
-static TRAINS_TYPEERROR: &str = "trains is not of type Array";
-static TRAIN_MISSING: &str = "train is missing";
-static TRAIN_TYPEERROR: &str = "train is not of type String";
-static COUNT_MISSING: &str = "count is missing";
-static COUNT_TYPEERROR: &str = "count is not of type Integer";
-
-/// Represents the trains and their respective counts avaliable in a game
-#[derive(Clone, Debug)]
+/// Represents the trains and their respective counts avaliable in a game, along with the trains
+/// discarded to the open market
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TrainSet {
-    trains: HashSet<(TrainId, i32)>,
+    trains: Vec<(TrainId, i32)>,
+    market: Vec<TrainId>,
 }
 
 impl TrainSet {
-    pub(crate) fn from_toml(s: &str) -> Self {
-        let toml = s.parse::<Value>().expect(INVALID_TOML);
-        let mut trains = HashSet::new();
-        let trains_toml = toml.get("trains").expect(TRAINS_MISSING);
-        for value in trains_toml.as_array().expect(TRAINS_TYPEERROR) {
-            let train = value
-                .get("train")
-                .expect(TRAIN_MISSING)
-                .as_str()
-                .expect(TRAIN_TYPEERROR)
-                .parse::<TrainId>()
-                .unwrap();
-            let count = value
-                .get("count")
-                .expect(COUNT_MISSING)
-                .as_integer()
-                .expect(COUNT_TYPEERROR);
-            trains.insert((train, count as i32));
+    /// Returns the `TrainSet` described by the TOML in `s`, naming `file` in any `ConfigError`
+    pub fn from_toml(file: &str, s: &str) -> Result<Self, Vec<ConfigError>> {
+        let mut reader = TomlReader::new(file);
+        let train_set = reader
+            .parse(s)
+            .and_then(|toml| Self::read(&toml, &mut reader));
+        reader.finish(train_set)
+    }
+
+    fn read(toml: &Value, reader: &mut TomlReader) -> Option<Self> {
+        let mut trains = Vec::new();
+        let mut valid = true;
+        for (i, value) in reader.array(toml, "", "trains")?.iter().enumerate() {
+            let path = index_path("trains", i);
+            let train = reader.parsed::<TrainId>(value, &path, "train");
+            let count = match reader.integer(value, &path, "count") {
+                Some(count) if count < -1 => {
+                    reader.error(&key_path(&path, "count"), format!("is negative: {}", count));
+                    None
+                }
+                count => count.map(|c| c as i32),
+            };
+            if let (Some(train), Some(count)) = (train, count) {
+                if trains.iter().any(|&(t, _)| t == train) {
+                    reader.error(&path, "is a duplicate train".to_string());
+                    valid = false;
+                }
+                trains.push((train, count));
+            } else {
+                valid = false;
+            }
+        }
+        if valid {
+            trains.sort_unstable();
+            Some(Self {
+                trains,
+                market: Vec::new(),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn remove_train(&mut self, train: TrainId) {
+        if let Some((_, count)) = self.trains.iter_mut().find(|(t, _)| *t == train) {
+            if *count > 0 {
+                *count -= 1;
+            }
+        }
+    }
+
+    pub(crate) fn add_market_train(&mut self, train: TrainId) {
+        self.market.push(train);
+        self.market.sort_unstable();
+    }
+
+    pub(crate) fn remove_market_train(&mut self, train: TrainId) {
+        if let Some(i) = self.market.iter().position(|&t| t == train) {
+            self.market.remove(i);
         }
-        Self { trains }
+    }
+
+    pub(crate) fn rust(&mut self, train: TrainId) {
+        self.market.retain(|&t| t != train);
+    }
+
+    /// Returns the next train available to buy from the depot of this `TrainSet`, if any
+    pub fn next_train(&self) -> Option<TrainId> {
+        self.trains
+            .iter()
+            .find(|&&(_, count)| count != 0)
+            .map(|&(train, _)| train)
+    }
+
+    /// Returns the trains and their respective counts in the depot of this `TrainSet`, where a
+    /// count of -1 means unlimited
+    pub fn trains(&self) -> &[(TrainId, i32)] {
+        &self.trains
+    }
+
+    /// Returns the trains in the open market of this `TrainSet`
+    pub fn market(&self) -> &[TrainId] {
+        &self.market
     }
 }
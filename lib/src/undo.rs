@@ -0,0 +1,157 @@
+use crate::{ActionError, Game};
+use serde::{Deserialize, Serialize};
+
+/// Represents which actions of a game may be undone
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UndoPolicy {
+    /// Only the player who performed the last action may undo it, until the next action is applied
+    #[default]
+    OwnTurn,
+    /// Any action may be undone, e.g. for hot-seat games
+    Unrestricted,
+}
+
+impl Game {
+    /// Returns the undo policy of this `Game`
+    pub fn undo_policy(&self) -> UndoPolicy {
+        self.undo_policy
+    }
+
+    /// Sets the undo policy of this `Game` to `undo_policy`
+    pub fn set_undo_policy(&mut self, undo_policy: UndoPolicy) {
+        self.undo_policy = undo_policy;
+    }
+
+    /// Returns whether undoing the last action by `player` is allowed
+    pub fn undo_allowed(&self, player: usize) -> bool {
+        self.check_undo(player).is_ok()
+    }
+
+    /// Returns `Ok` if undoing the last action by `player` is allowed, otherwise the reason it is
+    /// not
+    ///
+    /// With `UndoPolicy::OwnTurn` only the player who performed the last action may undo it, which
+    /// they may do until the next action is applied.
+    pub fn check_undo(&self, player: usize) -> Result<(), ActionError> {
+        let &(last_player, _) = self.log.last().ok_or(ActionError::NothingToUndo)?;
+        if self.start.is_none() {
+            Err(ActionError::NothingToUndo)
+        } else if self.undo_policy == UndoPolicy::OwnTurn && player != last_player {
+            Err(ActionError::NotYourTurn)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Undoes the last action by `player`, which may then be redone until another action is
+    /// applied
+    pub fn undo(&mut self, player: usize) -> Result<(), ActionError> {
+        self.check_undo(player)?;
+        let mut log = self.log.clone();
+        let mut redo_log = self.redo_log.clone();
+        redo_log.push(log.pop().ok_or(ActionError::NothingToUndo)?);
+        let mut game = (**self.start.as_ref().ok_or(ActionError::NothingToUndo)?).clone();
+        for &(_, action) in &log {
+            game.perform(action)?;
+        }
+        game.redo_log = redo_log;
+        game.undo_policy = self.undo_policy;
+        game.start = self.start.take();
+        *self = game;
+        Ok(())
+    }
+
+    /// Returns whether redoing the last undone action is allowed
+    pub fn redo_allowed(&self) -> bool {
+        self.check_redo().is_ok()
+    }
+
+    /// Returns `Ok` if redoing the last undone action is allowed, otherwise the reason it is not
+    pub fn check_redo(&self) -> Result<(), ActionError> {
+        let &(_, action) = self.redo_log.last().ok_or(ActionError::NothingToRedo)?;
+        self.check_action(action)
+    }
+
+    /// Redoes the last undone action
+    pub fn redo(&mut self) -> Result<(), ActionError> {
+        self.check_redo()?;
+        let (_, action) = self.redo_log.pop().unwrap();
+        self.perform(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::tests;
+    use crate::{Action, ActionError, Game, PrivComId, UndoPolicy};
+
+    fn played_game() -> Game {
+        let mut game = tests::game(4);
+        game.apply(Action::BuyCheapestPriv).unwrap();
+        game.apply(Action::BidPriv {
+            private: PrivComId::BAndO,
+            amount: 105,
+        })
+        .unwrap();
+        game.apply(Action::Pass).unwrap();
+        game
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut game = played_game();
+        assert_eq!(game.undo(2), Ok(()));
+        assert_eq!(game.log().len(), 2);
+        assert_eq!(game.current_player(), 2);
+        assert_eq!(game.undo(1), Ok(()));
+        assert_eq!(game.current_player(), 1);
+        assert_eq!(game.players[1].capital(), 600);
+        assert_eq!(game.redo(), Ok(()));
+        assert_eq!(game.redo(), Ok(()));
+        assert_eq!(game, played_game());
+    }
+
+    #[test]
+    fn apply_clears_redo() {
+        let mut game = played_game();
+        game.undo(2).unwrap();
+        assert_eq!(game.check_redo(), Ok(()));
+        game.apply(Action::BuyCheapestPriv).unwrap();
+        assert_eq!(game.check_redo(), Err(ActionError::NothingToRedo));
+    }
+
+    #[test]
+    fn undo_after_loading_snapshot() {
+        let mut game = Game::from_json(&played_game().to_json()).unwrap();
+        assert_eq!(game.undo(2), Ok(()));
+        assert_eq!(game.log().len(), 2);
+    }
+
+    #[test]
+    fn nothing_to_undo_or_redo_is_rejected() {
+        let game = tests::game(4);
+        assert_eq!(game.check_undo(0), Err(ActionError::NothingToUndo));
+        assert_eq!(game.check_redo(), Err(ActionError::NothingToRedo));
+    }
+
+    #[test]
+    fn own_turn_policy_rejects_undo_by_other_players() {
+        let mut game = played_game();
+        assert_eq!(game.undo_policy(), UndoPolicy::OwnTurn);
+        assert_eq!(game.check_undo(3), Err(ActionError::NotYourTurn));
+        assert_eq!(game.check_undo(1), Err(ActionError::NotYourTurn));
+        game.undo(2).unwrap();
+        assert_eq!(game.check_undo(2), Err(ActionError::NotYourTurn));
+        assert_eq!(game.check_undo(1), Ok(()));
+    }
+
+    #[test]
+    fn unrestricted_policy_allows_undo_by_any_player() {
+        let mut game = played_game();
+        game.set_undo_policy(UndoPolicy::Unrestricted);
+        assert_eq!(game.undo(3), Ok(()));
+        assert_eq!(game.undo(3), Ok(()));
+        assert_eq!(game.undo(3), Ok(()));
+        assert_eq!(game.check_undo(3), Err(ActionError::NothingToUndo));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::lobby::Lobby;
+use crate::protocol::{Request, Response};
+use lib::render;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tungstenite::error::ProtocolError;
+use tungstenite::{Error, Message};
+
+static POLL_INTERVAL: Duration = Duration::from_millis(50);
+static JSON: &str = "application/json";
+static SVG: &str = "image/svg+xml";
+static HTML: &str = "text/html; charset=utf-8";
+
+/// The browser client served at `/`
+static CLIENT: &str = include_str!("client.html");
+
+/// Serves `stream` as a WebSocket client if it asks for an upgrade, otherwise as an HTTP client
+pub fn handle(stream: TcpStream, lobby: Arc<Mutex<Lobby>>) -> io::Result<()> {
+    let head = peek_head(&stream)?;
+    let text = String::from_utf8_lossy(&head);
+    if text.to_ascii_lowercase().contains("upgrade: websocket") {
+        handle_web_socket(stream, lobby)
+    } else {
+        handle_http(stream, &head, &lobby)
+    }
+}
+
+fn peek_head(stream: &TcpStream) -> io::Result<Vec<u8>> {
+    let mut buf = [0; 4096];
+    loop {
+        let len = stream.peek(&mut buf)?;
+        if let Some(end) = buf[..len].windows(4).position(|w| w == b"\r\n\r\n") {
+            return Ok(buf[..end + 4].to_vec());
+        } else if len == 0 || len == buf.len() {
+            return Ok(buf[..len].to_vec());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn handle_web_socket(stream: TcpStream, lobby: Arc<Mutex<Lobby>>) -> io::Result<()> {
+    let mut socket = tungstenite::accept(stream).map_err(|e| io::Error::other(e.to_string()))?;
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    let (sender, receiver) = mpsc::channel();
+    let mut session = lobby.lock().unwrap().connect(sender);
+    let result = loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => match serde_json::from_str::<Request>(&text) {
+                Ok(request) => lobby.lock().unwrap().handle(&mut session, request),
+                Err(err) => session_error(&mut socket, err.to_string()),
+            },
+            Ok(Message::Close(_)) => break Ok(()),
+            Ok(_) => (),
+            Err(Error::Io(err))
+                if err.kind() == ErrorKind::WouldBlock || err.kind() == ErrorKind::TimedOut => {}
+            Err(Error::ConnectionClosed)
+            | Err(Error::Protocol(ProtocolError::ResetWithoutClosingHandshake)) => break Ok(()),
+            Err(err) => break Err(io::Error::other(err.to_string())),
+        }
+        if let Err(err) = receiver
+            .try_iter()
+            .try_for_each(|message| socket.send(Message::Text(message)).map_err(Box::new))
+        {
+            break Err(io::Error::other(err.to_string()));
+        }
+    };
+    lobby.lock().unwrap().disconnect(&session);
+    result
+}
+
+fn session_error(socket: &mut tungstenite::WebSocket<TcpStream>, message: String) {
+    socket
+        .send(Message::Text(Response::Error { message }.to_json()))
+        .ok();
+}
+
+fn handle_http(mut stream: TcpStream, head: &[u8], lobby: &Mutex<Lobby>) -> io::Result<()> {
+    stream.read_exact(&mut vec![0; head.len()])?;
+    let head = String::from_utf8_lossy(head);
+    let mut request_line = head.lines().next().unwrap_or("").split_whitespace();
+    let (method, path) = (request_line.next(), request_line.next().unwrap_or(""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let lobby = lobby.lock().unwrap();
+    let id = |id: &str| id.parse::<u64>().ok();
+    let body = match (method, segments.as_slice()) {
+        (Some("GET"), [""]) | (Some("GET"), ["index.html"]) => Some((HTML, CLIENT.to_string())),
+        (Some("GET"), ["games"]) => {
+            Some((JSON, serde_json::to_string(&lobby.summaries()).unwrap()))
+        }
+        (Some("GET"), ["games", game]) => id(game)
+            .and_then(|id| lobby.state(id))
+            .map(|state| (JSON, state.to_string())),
+        (Some("GET"), ["games", game, "map.svg"]) => id(game)
+            .and_then(|id| lobby.game(id))
+            .map(|game| (SVG, render::map_svg(game.map()))),
+        (Some("GET"), ["games", game, "stock_chart.svg"]) => id(game)
+            .and_then(|id| lobby.game(id))
+            .map(|game| (SVG, render::stock_chart_svg(game.stock_chart()))),
+        (Some("GET"), ["games", game, "par_track.svg"]) => id(game)
+            .and_then(|id| lobby.game(id))
+            .map(|game| (SVG, render::par_track_svg(game.par_track()))),
+        (Some("GET"), ["games", game, "charters.html"]) => id(game)
+            .and_then(|id| lobby.game(id))
+            .map(|game| (HTML, render::charters_html(game))),
+        _ => None,
+    };
+    drop(lobby);
+    let (status, (content_type, body)) = match body {
+        Some(body) => ("200 OK", body),
+        None => (
+            "404 Not Found",
+            (JSON, Response::error("not found").to_json()),
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
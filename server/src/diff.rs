@@ -0,0 +1,130 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// A change between two JSON values as a JSON Patch operation
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Change {
+    /// Adds `value` at `path`
+    Add { path: String, value: Value },
+    /// Removes the value at `path`
+    Remove { path: String },
+    /// Replaces the value at `path` with `value`
+    Replace { path: String, value: Value },
+}
+
+/// Returns the changes that turn `old` into `new`
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_at(String::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_at(path: String, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let key_path = format!("{}/{}", path, escape(key));
+                match new.get(key) {
+                    Some(new_value) => diff_at(key_path, old_value, new_value, changes),
+                    None => changes.push(Change::Remove { path: key_path }),
+                }
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    changes.push(Change::Add {
+                        path: format!("{}/{}", path, escape(key)),
+                        value: new_value.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => {
+            for (i, (old_value, new_value)) in old.iter().zip(new).enumerate() {
+                diff_at(format!("{}/{}", path, i), old_value, new_value, changes);
+            }
+        }
+        (old, new) if old != new => changes.push(Change::Replace {
+            path,
+            value: new.clone(),
+        }),
+        _ => (),
+    }
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, Change};
+    use serde_json::json;
+
+    #[test]
+    fn equal_values_have_no_changes() {
+        let value = json!({"a": [1, {"b": 2}], "c": "d"});
+        assert_eq!(diff(&value, &value), vec![]);
+    }
+
+    #[test]
+    fn object_keys_are_added_removed_and_replaced() {
+        let old = json!({"a": 1, "b": {"c": 2, "d": 3}});
+        let new = json!({"b": {"c": 4, "e": 5}, "f": 6});
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                Change::Remove {
+                    path: "/a".to_string()
+                },
+                Change::Replace {
+                    path: "/b/c".to_string(),
+                    value: json!(4)
+                },
+                Change::Remove {
+                    path: "/b/d".to_string()
+                },
+                Change::Add {
+                    path: "/b/e".to_string(),
+                    value: json!(5)
+                },
+                Change::Add {
+                    path: "/f".to_string(),
+                    value: json!(6)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn arrays_are_diffed_by_index_or_replaced() {
+        let old = json!({"a": [1, 2], "b": [1]});
+        let new = json!({"a": [1, 3], "b": [1, 2]});
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                Change::Replace {
+                    path: "/a/1".to_string(),
+                    value: json!(3)
+                },
+                Change::Replace {
+                    path: "/b".to_string(),
+                    value: json!([1, 2])
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_are_escaped() {
+        let old = json!({"a/b~c": 1});
+        let new = json!({"a/b~c": 2});
+        assert_eq!(
+            diff(&old, &new),
+            vec![Change::Replace {
+                path: "/a~1b~0c".to_string(),
+                value: json!(2)
+            }]
+        );
+    }
+}
@@ -0,0 +1,447 @@
+use crate::diff::diff;
+use crate::protocol::{GameSummary, Request, Response};
+use lib::{Game, GameConfig, UndoPolicy};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+
+/// The games of the server, saved as JSON files in a directory
+#[derive(Debug)]
+pub struct Lobby {
+    dir: PathBuf,
+    next_game: u64,
+    next_client: u64,
+    rooms: BTreeMap<u64, Room>,
+}
+
+/// The state of a connected client
+#[derive(Debug)]
+pub struct Session {
+    client: u64,
+    sender: Sender<String>,
+    game: Option<u64>,
+    seat: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Room {
+    game: Game,
+    seats: Vec<Option<String>>,
+    clients: Vec<(u64, Sender<String>)>,
+    state: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    seats: Vec<Option<String>>,
+    game: Value,
+}
+
+impl Lobby {
+    /// Returns a `Lobby` with the games saved in `dir`, creating `dir` if it does not exist
+    pub fn load(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let mut rooms = BTreeMap::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let id = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse().ok());
+            if let (Some(id), Some("json")) = (id, path.extension().and_then(|s| s.to_str())) {
+                match Room::load(&path) {
+                    Ok(room) => {
+                        rooms.insert(id, room);
+                    }
+                    Err(err) => eprintln!("{}: {}", path.display(), err),
+                }
+            }
+        }
+        Ok(Self {
+            dir,
+            next_game: rooms.keys().next_back().map_or(1, |id| id + 1),
+            next_client: 0,
+            rooms,
+        })
+    }
+
+    /// Returns a `Session` for a new client receiving messages through `sender`
+    pub fn connect(&mut self, sender: Sender<String>) -> Session {
+        self.next_client += 1;
+        Session {
+            client: self.next_client,
+            sender,
+            game: None,
+            seat: None,
+        }
+    }
+
+    /// Removes the client of `session` from its game
+    pub fn disconnect(&mut self, session: &Session) {
+        if let Some(room) = session.game.and_then(|id| self.rooms.get_mut(&id)) {
+            room.clients.retain(|&(client, _)| client != session.client);
+        }
+    }
+
+    /// Returns the summaries of the games in this `Lobby`
+    pub fn summaries(&self) -> Vec<GameSummary> {
+        self.rooms
+            .iter()
+            .map(|(&id, room)| GameSummary {
+                id,
+                seats: room.seats.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the state of game `id`, if any
+    pub fn state(&self, id: u64) -> Option<&Value> {
+        self.rooms.get(&id).map(|room| &room.state)
+    }
+
+    /// Returns game `id`, if any
+    pub fn game(&self, id: u64) -> Option<&Game> {
+        self.rooms.get(&id).map(|room| &room.game)
+    }
+
+    /// Handles `request` from the client of `session`
+    pub fn handle(&mut self, session: &mut Session, request: Request) {
+        let response = match request {
+            Request::ListGames => Ok(Some(Response::Games {
+                games: self.summaries(),
+            })),
+            Request::CreateGame {
+                players,
+                undo_policy,
+            } => self.create_game(players, undo_policy).map(Some),
+            Request::JoinGame { game, name } => self.join_game(session, game, Some(name)).map(Some),
+            Request::WatchGame { game } => self.join_game(session, game, None).map(Some),
+            Request::Action { action } => self.update_game(session, |game, seat| {
+                game.check_current_player(seat)?;
+                game.apply(action)
+            }),
+            Request::Undo => self.update_game(session, |game, seat| game.undo(seat)),
+            Request::Redo => self.update_game(session, |game, seat| {
+                if game.undo_policy() == UndoPolicy::OwnTurn {
+                    game.check_current_player(seat)?;
+                }
+                game.redo()
+            }),
+        };
+        match response {
+            Ok(Some(response)) => session.send(&response),
+            Ok(None) => (),
+            Err(message) => session.send(&Response::Error { message }),
+        }
+    }
+
+    fn create_game(&mut self, players: usize, undo_policy: UndoPolicy) -> Result<Response, String> {
+        let mut game = Game::with_config(players, &GameConfig::new()).map_err(|errors| {
+            errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })?;
+        game.set_undo_policy(undo_policy);
+        let id = self.next_game;
+        self.next_game += 1;
+        let room = Room::new(game, vec![None; players]);
+        room.save(&self.path(id)).map_err(|e| e.to_string())?;
+        self.rooms.insert(id, room);
+        Ok(Response::Created { game: id })
+    }
+
+    fn join_game(
+        &mut self,
+        session: &mut Session,
+        id: u64,
+        name: Option<String>,
+    ) -> Result<Response, String> {
+        if !self.rooms.contains_key(&id) {
+            return Err(format!("game {} does not exist", id));
+        }
+        let path = self.path(id);
+        self.disconnect(session);
+        let room = self.rooms.get_mut(&id).unwrap();
+        session.game = Some(id);
+        session.seat = None;
+        room.clients.push((session.client, session.sender.clone()));
+        if let Some(name) = name {
+            let seat = match room.seats.iter().position(|s| s.as_ref() == Some(&name)) {
+                Some(seat) => seat,
+                None => {
+                    let seat = room
+                        .seats
+                        .iter()
+                        .position(|s| s.is_none())
+                        .ok_or("game is full")?;
+                    room.seats[seat] = Some(name);
+                    if let Err(e) = room.save(&path) {
+                        room.seats[seat] = None;
+                        return Err(e.to_string());
+                    }
+                    seat
+                }
+            };
+            session.seat = Some(seat);
+            session.send(&Response::Joined { game: id, seat });
+        }
+        Ok(Response::State {
+            game: id,
+            state: room.state.clone(),
+        })
+    }
+
+    fn update_game(
+        &mut self,
+        session: &Session,
+        f: impl FnOnce(&mut Game, usize) -> Result<(), lib::ActionError>,
+    ) -> Result<Option<Response>, String> {
+        let id = session.game.ok_or("no game is joined")?;
+        let seat = session.seat.ok_or("no seat is taken")?;
+        let path = self.path(id);
+        let room = self.rooms.get_mut(&id).unwrap();
+        let bank_broken = room.game.bank().is_broken();
+        let mut game = room.game.clone();
+        f(&mut game, seat).map_err(|e| e.to_string())?;
+        let previous = mem::replace(&mut room.game, game);
+        if let Err(e) = room.save(&path) {
+            room.game = previous;
+            return Err(e.to_string());
+        }
+        room.broadcast(id);
+        if !bank_broken && room.game.bank().is_broken() {
+            room.notify(&Response::BankBroken { game: id });
+        }
+        Ok(None)
+    }
+
+    fn path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+impl Session {
+    fn send(&self, response: &Response) {
+        self.sender.send(response.to_json()).ok();
+    }
+}
+
+impl Room {
+    fn new(game: Game, seats: Vec<Option<String>>) -> Self {
+        let state = serde_json::to_value(&game).unwrap();
+        Self {
+            game,
+            seats,
+            clients: Vec::new(),
+            state,
+        }
+    }
+
+    fn load(path: &Path) -> Result<Self, String> {
+        let s = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let saved: SavedGame = serde_json::from_str(&s).map_err(|e| e.to_string())?;
+        let game = Game::from_json(&saved.game.to_string()).map_err(|e| e.to_string())?;
+        Ok(Self::new(game, saved.seats))
+    }
+
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let saved = SavedGame {
+            seats: self.seats.clone(),
+            game: serde_json::from_str(&self.game.to_json()).unwrap(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(&saved).unwrap())?;
+        fs::rename(tmp, path)
+    }
+
+    fn broadcast(&mut self, id: u64) {
+        let state = serde_json::to_value(&self.game).unwrap();
+        let changes = diff(&self.state, &state);
+        self.state = state;
+        self.notify(&Response::Diff { game: id, changes });
+    }
+
+    fn notify(&mut self, response: &Response) {
+        let message = response.to_json();
+        self.clients
+            .retain(|(_, sender)| sender.send(message.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lobby, Session};
+    use crate::protocol::Request;
+    use lib::{Action, UndoPolicy};
+    use serde_json::Value;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::mpsc::{self, Receiver};
+
+    fn lobby_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("lobby-{}-{}", process::id(), name));
+        fs::remove_dir_all(&dir).ok();
+        dir
+    }
+
+    fn connect(lobby: &mut Lobby) -> (Session, Receiver<String>) {
+        let (sender, receiver) = mpsc::channel();
+        (lobby.connect(sender), receiver)
+    }
+
+    fn responses(receiver: &Receiver<String>) -> Vec<Value> {
+        receiver
+            .try_iter()
+            .map(|message| serde_json::from_str(&message).unwrap())
+            .collect()
+    }
+
+    fn response_types(receiver: &Receiver<String>) -> Vec<String> {
+        responses(receiver)
+            .iter()
+            .map(|response| response["type"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn create_game(lobby: &mut Lobby, session: &mut Session, players: usize) {
+        lobby.handle(
+            session,
+            Request::CreateGame {
+                players,
+                undo_policy: UndoPolicy::OwnTurn,
+            },
+        );
+    }
+
+    fn join_game(lobby: &mut Lobby, session: &mut Session, name: &str) {
+        lobby.handle(
+            session,
+            Request::JoinGame {
+                game: 1,
+                name: name.to_string(),
+            },
+        );
+    }
+
+    #[test]
+    fn create_game_saves_it() {
+        let dir = lobby_dir("create");
+        let mut lobby = Lobby::load(&dir).unwrap();
+        let (mut session, receiver) = connect(&mut lobby);
+        create_game(&mut lobby, &mut session, 7);
+        assert_eq!(response_types(&receiver), ["error"]);
+        create_game(&mut lobby, &mut session, 4);
+        assert_eq!(responses(&receiver)[0]["game"], 1);
+        assert!(dir.join("1.json").exists());
+        lobby.handle(&mut session, Request::ListGames);
+        assert_eq!(
+            responses(&receiver)[0]["games"][0]["seats"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn join_game_takes_seats_by_name() {
+        let dir = lobby_dir("join");
+        let mut lobby = Lobby::load(&dir).unwrap();
+        let (mut alice, alice_receiver) = connect(&mut lobby);
+        let (mut bob, bob_receiver) = connect(&mut lobby);
+        let (mut carol, carol_receiver) = connect(&mut lobby);
+        create_game(&mut lobby, &mut alice, 2);
+        responses(&alice_receiver);
+        join_game(&mut lobby, &mut alice, "Alice");
+        assert_eq!(response_types(&alice_receiver), ["joined", "state"]);
+        join_game(&mut lobby, &mut bob, "Bob");
+        assert_eq!(responses(&bob_receiver)[0]["seat"], 1);
+        join_game(&mut lobby, &mut carol, "Carol");
+        assert_eq!(response_types(&carol_receiver), ["error"]);
+        join_game(&mut lobby, &mut carol, "Alice");
+        assert_eq!(responses(&carol_receiver)[0]["seat"], 0);
+        lobby.handle(&mut carol, Request::WatchGame { game: 1 });
+        assert_eq!(response_types(&carol_receiver), ["state"]);
+        lobby.handle(
+            &mut carol,
+            Request::Action {
+                action: Action::Pass,
+            },
+        );
+        assert_eq!(response_types(&carol_receiver), ["error"]);
+        let lobby = Lobby::load(&dir).unwrap();
+        assert_eq!(
+            lobby.summaries()[0].seats,
+            [Some("Alice".to_string()), Some("Bob".to_string())]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn actions_are_checked_saved_and_broadcast() {
+        let dir = lobby_dir("action");
+        let mut lobby = Lobby::load(&dir).unwrap();
+        let (mut alice, alice_receiver) = connect(&mut lobby);
+        let (mut bob, bob_receiver) = connect(&mut lobby);
+        create_game(&mut lobby, &mut alice, 2);
+        join_game(&mut lobby, &mut alice, "Alice");
+        join_game(&mut lobby, &mut bob, "Bob");
+        responses(&alice_receiver);
+        responses(&bob_receiver);
+        let buy = Request::Action {
+            action: Action::BuyCheapestPriv,
+        };
+        lobby.handle(&mut bob, buy.clone());
+        assert_eq!(response_types(&bob_receiver), ["error"]);
+        lobby.handle(&mut alice, buy);
+        assert_eq!(response_types(&alice_receiver), ["diff"]);
+        assert_eq!(response_types(&bob_receiver), ["diff"]);
+        let state = lobby.state(1).unwrap().clone();
+        assert_eq!(state["current_player"], 1);
+        assert_eq!(Lobby::load(&dir).unwrap().state(1), Some(&state));
+        lobby.handle(&mut bob, Request::Undo);
+        assert_eq!(response_types(&bob_receiver), ["error"]);
+        lobby.handle(&mut alice, Request::Undo);
+        assert_eq!(response_types(&bob_receiver), ["diff"]);
+        assert_eq!(lobby.state(1).unwrap()["current_player"], 0);
+        lobby.handle(&mut alice, Request::Redo);
+        assert_eq!(lobby.state(1), Some(&state));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_saves_are_rolled_back() {
+        let dir = lobby_dir("rollback");
+        let mut lobby = Lobby::load(&dir).unwrap();
+        let (mut alice, alice_receiver) = connect(&mut lobby);
+        create_game(&mut lobby, &mut alice, 2);
+        join_game(&mut lobby, &mut alice, "Alice");
+        responses(&alice_receiver);
+        let state = lobby.state(1).unwrap().clone();
+        fs::remove_dir_all(&dir).unwrap();
+        lobby.handle(
+            &mut alice,
+            Request::Action {
+                action: Action::BuyCheapestPriv,
+            },
+        );
+        assert_eq!(response_types(&alice_receiver), ["error"]);
+        assert_eq!(lobby.state(1), Some(&state));
+        assert_eq!(lobby.rooms[&1].game.log().len(), 0);
+        let (mut bob, bob_receiver) = connect(&mut lobby);
+        join_game(&mut lobby, &mut bob, "Bob");
+        assert_eq!(response_types(&bob_receiver), ["error"]);
+        assert_eq!(lobby.summaries()[0].seats[1], None);
+    }
+}
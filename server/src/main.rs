@@ -1,6 +1,59 @@
-use lib::Game;
+//! Local game server for 18Chesapeake.
+//!
+//! Usage: `server [address] [directory]`, listening on `127.0.0.1:1830` and saving games to
+//! `games` by default.
+//!
+//! Clients connect with WebSocket and send JSON requests tagged by `type`, e.g.
+//! `{"type":"create_game","players":4}`, `{"type":"join_game","game":1,"name":"Alice"}` and
+//! `{"type":"action","action":"Pass"}`. Every client of a game receives the changes to its state
+//! as JSON Patch operations after each action, and a `bank_broken` message when the bank breaks.
+//! Plain HTTP `GET /` serves a browser client, `GET /games` and `GET /games/<id>` return the games
+//! and the state of a game, and `GET /games/<id>/map.svg`, `/stock_chart.svg`, `/par_track.svg`
+//! and `/charters.html` return images of its map, stock chart and par track and the charters of
+//! its companies and players.
+
+mod connection;
+mod diff;
+mod lobby;
+mod protocol;
+
+use lobby::Lobby;
+use std::env;
+use std::net::TcpListener;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 fn main() {
-    let game = Game::new(4);
-    println!("{:#?}", game);
+    let mut args = env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:1830".to_string());
+    let dir = args.next().unwrap_or_else(|| "games".to_string());
+    let lobby = match Lobby::load(&dir) {
+        Ok(lobby) => Arc::new(Mutex::new(lobby)),
+        Err(err) => {
+            eprintln!("{}: {}", dir, err);
+            process::exit(1);
+        }
+    };
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("{}: {}", address, err);
+            process::exit(1);
+        }
+    };
+    println!("listening on {}", address);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let lobby = Arc::clone(&lobby);
+                thread::spawn(move || {
+                    if let Err(err) = connection::handle(stream, lobby) {
+                        eprintln!("{}", err);
+                    }
+                });
+            }
+            Err(err) => eprintln!("{}", err),
+        }
+    }
 }
@@ -0,0 +1,69 @@
+use crate::diff::Change;
+use lib::{Action, UndoPolicy};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A message sent by a client
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Lists the games of the server
+    ListGames,
+    /// Creates a game for `players` players
+    CreateGame {
+        players: usize,
+        #[serde(default)]
+        undo_policy: UndoPolicy,
+    },
+    /// Takes the first free seat of `game` as `name`, or the seat already taken by `name`
+    JoinGame { game: u64, name: String },
+    /// Follows `game` without taking a seat
+    WatchGame { game: u64 },
+    /// Performs `action` in the joined game
+    Action { action: Action },
+    /// Undoes the last action of the joined game
+    Undo,
+    /// Redoes the last undone action of the joined game
+    Redo,
+}
+
+/// A message sent by the server
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    /// The games of the server
+    Games { games: Vec<GameSummary> },
+    /// A game was created
+    Created { game: u64 },
+    /// The client was seated as player `seat` of `game`
+    Joined { game: u64, seat: usize },
+    /// The full state of `game`
+    State { game: u64, state: Value },
+    /// The changes to the state of `game` since the last message about it
+    Diff { game: u64, changes: Vec<Change> },
+    /// The bank of `game` broke, so the game ends after the current set of operating rounds
+    BankBroken { game: u64 },
+    /// The request was rejected
+    Error { message: String },
+}
+
+/// A short description of a game on the server
+#[derive(Clone, Debug, Serialize)]
+pub struct GameSummary {
+    pub id: u64,
+    pub seats: Vec<Option<String>>,
+}
+
+impl Response {
+    /// Returns an error response with `message`
+    pub fn error(message: impl ToString) -> Self {
+        Response::Error {
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns this `Response` as JSON text
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
@@ -0,0 +1,128 @@
+//! Terminal client for hot-seat games of 18Chesapeake.
+//!
+//! Usage: `tui [players | snapshot]`, starting a game for 4 players by default. Moves are chosen
+//! by number; `u` undoes, `r` redoes, `w <path>` saves the game as JSON and `q` quits.
+
+mod render;
+
+use lib::{Action, Game, GameConfig, LegalAction, UndoPolicy};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+fn main() {
+    let mut game = match env::args().nth(1) {
+        Some(arg) => match arg.parse() {
+            Ok(players) => new_game(players),
+            Err(_) => load_game(&arg),
+        },
+        None => new_game(4),
+    };
+    game.set_undo_policy(UndoPolicy::Unrestricted);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut status = String::new();
+    loop {
+        let legal_actions = game.legal_actions();
+        print!("\x1b[2J\x1b[H{}", render::render(&game));
+        for (i, legal_action) in legal_actions.iter().enumerate() {
+            println!("{:>4}) {}", i + 1, render::describe(&game, legal_action));
+        }
+        if game.is_over() {
+            return;
+        } else if legal_actions.is_empty() {
+            println!("No legal moves are left");
+            return;
+        }
+        println!("{}", status);
+        print!("Player {}> ", game.current_player());
+        io::stdout().flush().unwrap();
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => return,
+        };
+        let mut words = line.split_whitespace();
+        let result = match (words.next(), words.next()) {
+            (Some("q"), _) => return,
+            (Some("u"), _) => game.undo(game.current_player()).map_err(|e| e.to_string()),
+            (Some("r"), _) => game.redo().map_err(|e| e.to_string()),
+            (Some("w"), Some(path)) => fs::write(path, game.to_json()).map_err(|e| e.to_string()),
+            (Some(n), amount) => match n.parse::<usize>() {
+                Ok(n) if (1..=legal_actions.len()).contains(&n) => {
+                    choose(&legal_actions[n - 1], amount, &mut lines)
+                        .and_then(|action| game.apply(action).map_err(|e| e.to_string()))
+                }
+                _ => Err(format!("{} is not a move", n)),
+            },
+            (None, _) => Ok(()),
+        };
+        status = result.err().unwrap_or_default();
+    }
+}
+
+fn choose(
+    legal_action: &LegalAction,
+    amount: Option<&str>,
+    lines: &mut impl Iterator<Item = io::Result<String>>,
+) -> Result<Action, String> {
+    let (min, max) = match *legal_action {
+        LegalAction::Action(action) => return Ok(action),
+        LegalAction::BidPriv { min, max, .. } => (min, max),
+        LegalAction::BuyPubComTrain { min, max, .. } => (min, max),
+        LegalAction::BuyPriv { min, max, .. } => (min, max),
+    };
+    let amount = match amount {
+        Some(amount) => amount.to_string(),
+        None => {
+            print!("Amount ({}-{})> ", min, max);
+            io::stdout().flush().unwrap();
+            match lines.next() {
+                Some(Ok(line)) => line,
+                _ => return Err("no amount".to_string()),
+            }
+        }
+    };
+    let amount = amount
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} is not an amount", amount.trim()))?;
+    Ok(match *legal_action {
+        LegalAction::BidPriv { private, .. } => Action::BidPriv { private, amount },
+        LegalAction::BuyPubComTrain { seller, train, .. } => Action::BuyPubComTrain {
+            seller,
+            train,
+            price: amount,
+        },
+        LegalAction::BuyPriv { private, .. } => Action::BuyPriv {
+            private,
+            price: amount,
+        },
+        LegalAction::Action(action) => action,
+    })
+}
+
+fn new_game(players: usize) -> Game {
+    match Game::with_config(players, &GameConfig::new()) {
+        Ok(game) => game,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{}", error);
+            }
+            process::exit(1);
+        }
+    }
+}
+
+fn load_game(path: &str) -> Game {
+    let result = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| Game::from_json(&s).map_err(|e| e.to_string()));
+    match result {
+        Ok(game) => game,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use lib::geography::{Hex, HexCoord, Stop};
+use lib::{Action, ColorId, EndReason, Game, LegalAction, PubComId, RoundId, TerrainId};
+use std::fmt::Write;
+
+static CELL_WIDTH: usize = 8;
+
+/// Returns the text rendering of `game`
+pub fn render(game: &Game) -> String {
+    let mut s = String::new();
+    render_round(&mut s, game);
+    render_players(&mut s, game);
+    render_pub_coms(&mut s, game);
+    render_stock_chart(&mut s, game);
+    render_par_track(&mut s, game);
+    render_map(&mut s, game);
+    s
+}
+
+/// Returns a description of `legal_action`
+pub fn describe(game: &Game, legal_action: &LegalAction) -> String {
+    match *legal_action {
+        LegalAction::Action(action) => describe_action(game, action),
+        LegalAction::BidPriv { private, min, max } => {
+            format!("Bid {}-{} on {}", min, max, private)
+        }
+        LegalAction::BuyPubComTrain {
+            seller,
+            train,
+            min,
+            max,
+        } => format!("Buy {}-train from {} for {}-{}", train, seller, min, max),
+        LegalAction::BuyPriv { private, min, max } => {
+            format!("Buy {} for {}-{}", private, min, max)
+        }
+    }
+}
+
+fn describe_action(game: &Game, action: Action) -> String {
+    match action {
+        Action::BidPriv { private, amount } => format!("Bid {} on {}", amount, private),
+        Action::BuyCheapestPriv => "Buy the cheapest private".to_string(),
+        Action::Pass => "Pass".to_string(),
+        Action::BuyIpoShare(pub_com) => format!("Buy {} share from the IPO", pub_com),
+        Action::BuyPoolShare(pub_com) => format!("Buy {} share from the pool", pub_com),
+        Action::BuyPresidency { pub_com, par } => format!("Par {} at {}", pub_com, par),
+        Action::SellShares { pub_com, count } => format!("Sell {} {} shares", count, pub_com),
+        Action::EndTurn => "End turn".to_string(),
+        Action::LayTile {
+            x,
+            y,
+            tile,
+            rotation,
+        } => format!(
+            "Lay tile {} at {} rotated {}",
+            tile,
+            HexCoord::new(x, y),
+            rotation
+        ),
+        Action::PlaceStation { x, y, city } => {
+            format!("Place station in city {} at {}", city, HexCoord::new(x, y))
+        }
+        Action::RunTrains { payout } => {
+            let revenue = game.routes().map_or(0, |routes| routes.revenue());
+            if payout {
+                format!("Run trains for {} and pay out", revenue)
+            } else {
+                format!("Run trains for {} and withhold", revenue)
+            }
+        }
+        Action::BuyTrain => match game.train_set().next_train() {
+            Some(train) => format!("Buy {}-train from the depot for {}", train, train.cost()),
+            None => "Buy train from the depot".to_string(),
+        },
+        Action::BuyMarketTrain(train) => {
+            format!(
+                "Buy {}-train from the open market for {}",
+                train,
+                train.cost()
+            )
+        }
+        Action::BuyPubComTrain {
+            seller,
+            train,
+            price,
+        } => format!("Buy {}-train from {} for {}", train, seller, price),
+        Action::DeclareBankruptcy => "Declare bankruptcy".to_string(),
+        Action::BuyPriv { private, price } => format!("Buy {} for {}", private, price),
+        Action::SetPar { pub_com, par } => format!("Set the par value of {} to {}", pub_com, par),
+        Action::LayPrivTile {
+            private,
+            x,
+            y,
+            tile,
+            rotation,
+        } => format!(
+            "Lay tile {} at {} rotated {} using {}",
+            tile,
+            HexCoord::new(x, y),
+            rotation,
+            private
+        ),
+    }
+}
+
+fn render_round(s: &mut String, game: &Game) {
+    write!(
+        s,
+        "Phase {:?}, bank {} - ",
+        game.phase(),
+        game.bank().capital()
+    )
+    .unwrap();
+    match game.round() {
+        RoundId::PrivAuction(priv_auction) => {
+            write!(s, "Private auction").unwrap();
+            if let Some(current) = priv_auction.current() {
+                write!(s, ", {} for {}", current, current.cost()).unwrap();
+            }
+            writeln!(s).unwrap();
+            for player in game.players() {
+                let mut bids: Vec<_> = priv_auction.bids(player).iter().collect();
+                bids.sort_by_key(|&(private, _)| private.cost());
+                for (private, bid) in bids {
+                    writeln!(s, "  Player {} bids {} on {}", player.id(), bid, private).unwrap();
+                }
+            }
+        }
+        RoundId::StockRound(_) => writeln!(s, "Stock round").unwrap(),
+        RoundId::OperatingRound(operating_round) => {
+            write!(
+                s,
+                "Operating round, {} more to go",
+                operating_round.operating_rounds_left()
+            )
+            .unwrap();
+            if let Some(pub_com) = operating_round.current() {
+                write!(s, ", {} {:?}", pub_com, operating_round.phase()).unwrap();
+            }
+            writeln!(s).unwrap();
+        }
+        RoundId::GameOver(reason) => {
+            match reason {
+                EndReason::BankBroken => writeln!(s, "Game over, the bank broke").unwrap(),
+                EndReason::Bankruptcy { pub_com, player } => writeln!(
+                    s,
+                    "Game over, player {} went bankrupt operating {}",
+                    player, pub_com
+                )
+                .unwrap(),
+            }
+            for standing in game.standings() {
+                writeln!(
+                    s,
+                    "  {}. Player {} with {} ({} cash, {} shares)",
+                    standing.rank(),
+                    standing.player(),
+                    standing.total(),
+                    standing.capital(),
+                    standing.share_value()
+                )
+                .unwrap();
+            }
+        }
+    }
+    writeln!(
+        s,
+        "Current player {}, priority player {}",
+        game.current_player(),
+        game.priority_player()
+    )
+    .unwrap();
+    writeln!(s).unwrap();
+}
+
+fn render_players(s: &mut String, game: &Game) {
+    write!(s, "{:<10}{:>7}", "Player", "Cash").unwrap();
+    for pub_com in PubComId::values() {
+        write!(s, "{:>6}", pub_com.to_string()).unwrap();
+    }
+    writeln!(s, "  Privates").unwrap();
+    for player in game.players() {
+        write!(s, "{:<10}{:>7}", player.id(), player.capital()).unwrap();
+        for pub_com in PubComId::values() {
+            write!(s, "{:>6}", shares(player.shares(), pub_com)).unwrap();
+        }
+        let mut privates: Vec<_> = player.priv_coms().iter().collect();
+        privates.sort_by_key(|private| private.cost());
+        let privates: Vec<_> = privates.iter().map(|p| p.to_string()).collect();
+        writeln!(s, "  {}", privates.join(" ")).unwrap();
+    }
+    write!(s, "{:<17}", "IPO").unwrap();
+    for pub_com in PubComId::values() {
+        write!(s, "{:>6}", shares(game.ipo(), pub_com)).unwrap();
+    }
+    writeln!(s).unwrap();
+    write!(s, "{:<17}", "Pool").unwrap();
+    for pub_com in PubComId::values() {
+        write!(s, "{:>6}", shares(game.pool(), pub_com)).unwrap();
+    }
+    writeln!(s).unwrap();
+    writeln!(s).unwrap();
+}
+
+fn shares(shares: &lib::economy::Shares, pub_com: PubComId) -> String {
+    let count = shares.count(pub_com);
+    if shares.contains_presidency(pub_com) {
+        format!("{}P", count)
+    } else if count == 0 {
+        "-".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+fn render_pub_coms(s: &mut String, game: &Game) {
+    writeln!(
+        s,
+        "{:<10}{:>10}{:>7}{:>7}{:>10}  Trains",
+        "Company", "President", "Cash", "Value", "Stations"
+    )
+    .unwrap();
+    for pub_com in PubComId::values() {
+        if let Some(floated) = game.pub_com(pub_com) {
+            let trains: Vec<_> = floated.trains().iter().map(|t| t.to_string()).collect();
+            writeln!(
+                s,
+                "{:<10}{:>10}{:>7}{:>7}{:>10}  {}",
+                pub_com.to_string(),
+                game.president(pub_com)
+                    .map_or("-".to_string(), |p| p.to_string()),
+                floated.capital(),
+                game.stock_chart().value(pub_com).unwrap_or(0),
+                floated.stations_left(),
+                trains.join(" ")
+            )
+            .unwrap();
+        }
+    }
+    let depot: Vec<_> = game
+        .train_set()
+        .trains()
+        .iter()
+        .map(|&(train, count)| {
+            if count < 0 {
+                train.to_string()
+            } else {
+                format!("{}x{}", count, train)
+            }
+        })
+        .collect();
+    let market: Vec<_> = game
+        .train_set()
+        .market()
+        .iter()
+        .map(|t| t.to_string())
+        .collect();
+    writeln!(
+        s,
+        "Depot: {}  Open market: {}",
+        depot.join(" "),
+        market.join(" ")
+    )
+    .unwrap();
+    writeln!(s).unwrap();
+}
+
+fn render_stock_chart(s: &mut String, game: &Game) {
+    writeln!(s, "Stock chart").unwrap();
+    let mut tokens: Vec<_> = PubComId::values()
+        .filter_map(|p| game.stock_chart().position(p).map(|pos| (pos, p)))
+        .collect();
+    tokens.sort_by_key(|&(pos, _)| pos);
+    for (row, values) in game.stock_chart().values().iter().enumerate() {
+        for (column, value) in values.iter().enumerate() {
+            let here: Vec<_> = tokens
+                .iter()
+                .filter(|&&((r, c, _), _)| r == row && c == column)
+                .map(|(_, p)| p.to_string())
+                .collect();
+            if here.is_empty() {
+                write!(s, "{:>4}", value).unwrap();
+            } else {
+                write!(s, "{:>4}[{}]", value, here.join(",")).unwrap();
+            }
+        }
+        writeln!(s).unwrap();
+    }
+    writeln!(s).unwrap();
+}
+
+fn render_par_track(s: &mut String, game: &Game) {
+    write!(s, "Par track:").unwrap();
+    for &par in game.par_track().values() {
+        let here: Vec<_> = PubComId::values()
+            .filter(|&p| game.par_track().value(p) == Some(par))
+            .map(|p| p.to_string())
+            .collect();
+        if here.is_empty() {
+            write!(s, " {}", par).unwrap();
+        } else {
+            write!(s, " {}[{}]", par, here.join(",")).unwrap();
+        }
+    }
+    writeln!(s).unwrap();
+    writeln!(s).unwrap();
+}
+
+fn render_map(s: &mut String, game: &Game) {
+    let map = game.map();
+    let mut lines = vec![String::new(); map.height() * 2 + 2];
+    for x in 0..map.width() {
+        let letters = HexCoord::new(x, 0).letters();
+        write!(lines[0], "{:^width$}", letters, width = CELL_WIDTH).unwrap();
+    }
+    for line in lines.iter_mut().skip(1) {
+        line.push_str(&" ".repeat(map.width() * CELL_WIDTH));
+    }
+    for x in 0..map.width() {
+        for y in 0..map.height() {
+            if let Some(hex) = map.hex(x, y) {
+                let line = 1 + y * 2 + x % 2;
+                let (top, bottom) = hex_cells(game, hex);
+                let range = x * CELL_WIDTH..(x + 1) * CELL_WIDTH;
+                lines[line].replace_range(range.clone(), &fit(&top));
+                lines[line + 1].replace_range(range, &fit(&bottom));
+            }
+        }
+    }
+    for (i, line) in lines.iter().enumerate() {
+        if i == 0 || i > map.height() * 2 {
+            writeln!(s, "   {}", line.trim_end()).unwrap();
+        } else {
+            writeln!(s, "{:>2} {}", i, line.trim_end()).unwrap();
+        }
+    }
+    writeln!(s).unwrap();
+}
+
+fn hex_cells(game: &Game, hex: &Hex) -> (String, String) {
+    let top = match (hex.tile(), hex.tile_id()) {
+        (Some(tile), _) if tile.color() == ColorId::Red => hex
+            .rails()
+            .iter()
+            .find_map(|r| match r.stop() {
+                Some(Stop::Location(location)) => {
+                    Some(format!("<{}>", location.value(game.phase())))
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| "<>".to_string()),
+        (Some(tile), _) if tile.color() == ColorId::Gray => "=====".to_string(),
+        (_, Some(id)) => format!("#{}", id),
+        _ => match hex.terrain() {
+            TerrainId::Plain => ".".to_string(),
+            TerrainId::River => format!("~{}", hex.terrain().cost()),
+            TerrainId::Mountain => format!("^{}", hex.terrain().cost()),
+        },
+    };
+    let cities = hex.cities();
+    let bottom = if !cities.is_empty() {
+        cities
+            .iter()
+            .map(|city| {
+                let mut stations: Vec<_> = city.stations().iter().map(|p| p.to_string()).collect();
+                stations.sort();
+                if city.spots() == 0 {
+                    format!("*{}", city.value())
+                } else if stations.is_empty() {
+                    format!("o{}", city.value())
+                } else {
+                    stations.join(",")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else if let Some(private) = hex.private() {
+        format!("({})", private)
+    } else {
+        String::new()
+    };
+    (top, bottom)
+}
+
+fn fit(s: &str) -> String {
+    let s: String = s.chars().take(CELL_WIDTH - 1).collect();
+    format!("{:<width$}", s, width = CELL_WIDTH)
+}